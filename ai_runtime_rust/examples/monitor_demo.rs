@@ -10,7 +10,11 @@ async fn main() {
     println!("🔍 GVPIE AI Runtime - System Monitor Demo\n");
     println!("==========================================\n");
 
-    let mut monitor = SystemMonitor::new();
+    // Sample whatever optional metrics `monitor.metrics` enables in the config files
+    let monitor_config = ai_runtime::config::Config::load()
+        .map(|config| config.monitor)
+        .unwrap_or_default();
+    let mut monitor = SystemMonitor::from_config(&monitor_config);
 
     // Capture a single snapshot
     println!("📊 Single Snapshot:");
@@ -66,4 +70,10 @@ fn print_metrics(metrics: &ai_runtime::SystemMetrics) {
         metrics.network_rx_bytes / 1024 / 1024,
         metrics.network_tx_bytes / 1024 / 1024
     );
+
+    let mut extra: Vec<_> = metrics.extra.iter().collect();
+    extra.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, value) in extra {
+        println!("  ➕ {}: {:.2}", name, value);
+    }
 }
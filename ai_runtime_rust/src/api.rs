@@ -1,15 +1,25 @@
 use axum::{
-    extract::{Path, State},
+    body::Bytes,
+    error_handling::HandleErrorLayer,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
 };
 use gvpie_core::PixelInstruction;
+use mime::Mime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
+use tower::{BoxError, ServiceBuilder};
 
 use crate::{
-    cartridges::Cartridge, AiRuntime, ExecutionBackend, PixelProgramRequest, PixelProgramResponse,
+    cartridges::{Cartridge, CodeFormat},
+    AiRuntime, AiRuntimeError, CanvasRegion, ExecutionBackend, PixelProgramRequest,
+    PixelProgramResponse,
 };
 
 #[derive(Debug, Clone)]
@@ -25,20 +35,27 @@ impl ApiServer {
     }
 
     pub fn router(runtime: Arc<AiRuntime>) -> Router {
-        Router::new()
+        let request_timeout = Duration::from_millis(runtime.config().api.request_timeout_ms);
+        let router = Router::new()
             .route("/health", get(Self::health))
             .route("/status", get(Self::system_status))
+            .route("/api/stats", get(Self::runtime_stats))
             .route("/api/execute", post(Self::execute_cartridge))
             .route(
                 "/api/cartridges",
                 get(Self::list_cartridges).post(Self::create_cartridge),
             )
+            .route("/api/cartridges/search", get(Self::search_cartridges))
             .route("/api/cartridges/:id", get(Self::get_cartridge))
             .route("/api/cartridges/:id", put(Self::update_cartridge))
             .route("/api/cartridges/:id", delete(Self::delete_cartridge))
+            .route("/api/cartridges/:id/history", get(Self::cartridge_history))
+            .route("/api/cartridges/:id/replay", post(Self::replay_cartridge))
             .route("/api/pixel/run", post(Self::execute_pixel_program))
             .route("/api/pixel/assemble", post(Self::assemble_pixel_program))
             .route("/api/pixel/backends", get(Self::list_pixel_backends))
+            .route("/api/pixel/diff", post(Self::diff_pixel_backends))
+            .route("/api/pixel/coverage", get(Self::pixel_opcode_coverage))
             // GVPIe Analysis endpoints
             .route("/api/gvpie/analyze", get(Self::analyze_gvpie_codebase))
             .route(
@@ -46,6 +63,7 @@ impl ApiServer {
                 get(Self::analyze_gvpie_component),
             )
             .route("/api/gvpie/suggestions", post(Self::get_gvpie_suggestions))
+            .route("/api/gvpie/review", get(Self::get_review_comments))
             .route(
                 "/api/gvpie/assistance",
                 get(Self::get_development_assistance),
@@ -54,7 +72,8 @@ impl ApiServer {
                 "/api/gvpie/predict-performance",
                 post(Self::predict_performance_impact),
             )
-            .with_state(runtime)
+            .with_state(runtime);
+        with_request_timeout(router, request_timeout)
     }
 
     pub fn into_router(self) -> Router {
@@ -72,14 +91,30 @@ impl ApiServer {
         Ok(())
     }
 
-    pub async fn health() -> &'static str {
-        "✅ AI Runtime Healthy"
+    /// Liveness probe: 200 with a component map, or 503 if a critical component is down
+    pub async fn health(
+        State(runtime): State<Arc<AiRuntime>>,
+    ) -> (StatusCode, Json<crate::HealthReport>) {
+        let report = runtime.health_check().await;
+        let status = if report.is_down() {
+            StatusCode::SERVICE_UNAVAILABLE
+        } else {
+            StatusCode::OK
+        };
+        (status, Json(report))
     }
 
     pub async fn list_cartridges(State(runtime): State<Arc<AiRuntime>>) -> Json<Vec<Cartridge>> {
         Json(runtime.list_cartridges().await)
     }
 
+    pub async fn search_cartridges(
+        State(runtime): State<Arc<AiRuntime>>,
+        Query(query): Query<SearchQuery>,
+    ) -> Json<Vec<Cartridge>> {
+        Json(runtime.search_cartridges(&query.q).await)
+    }
+
     pub async fn get_cartridge(
         State(runtime): State<Arc<AiRuntime>>,
         Path(id): Path<String>,
@@ -101,6 +136,29 @@ impl ApiServer {
         })
     }
 
+    pub async fn runtime_stats(
+        State(runtime): State<Arc<AiRuntime>>,
+        Query(query): Query<StatsQuery>,
+    ) -> Response {
+        match runtime.stats(query.hours).await {
+            Ok(stats) => Json(stats).into_response(),
+            Err(e) => {
+                let status = match e {
+                    AiRuntimeError::ValidationError(_) => StatusCode::BAD_REQUEST,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                (
+                    status,
+                    Json(ErrorResponse {
+                        success: false,
+                        error: e.to_string(),
+                    }),
+                )
+                    .into_response()
+            }
+        }
+    }
+
     pub async fn execute_cartridge(
         State(runtime): State<Arc<AiRuntime>>,
         Json(request): Json<ExecuteRequest>,
@@ -117,21 +175,144 @@ impl ApiServer {
         }
     }
 
+    pub async fn cartridge_history(
+        State(runtime): State<Arc<AiRuntime>>,
+        Path(id): Path<String>,
+    ) -> Result<Json<Vec<crate::CartridgeExecutionRecord>>, Json<ErrorResponse>> {
+        runtime.cartridge_history(&id).await.map(Json).map_err(|e| {
+            Json(ErrorResponse {
+                success: false,
+                error: e.to_string(),
+            })
+        })
+    }
+
+    pub async fn replay_cartridge(
+        State(runtime): State<Arc<AiRuntime>>,
+        Path(id): Path<String>,
+        Json(request): Json<ReplayRequest>,
+    ) -> Json<ReplayResponse> {
+        match runtime.replay_cartridge(&id, request.index).await {
+            Ok((record, result)) => {
+                let result_hash = crate::models::hash_bytes(&result.data);
+                Json(ReplayResponse {
+                    success: true,
+                    output: result.output,
+                    matches_original: result_hash == record.result_hash,
+                    result_hash,
+                    error: None,
+                })
+            }
+            Err(e) => Json(ReplayResponse {
+                success: false,
+                output: String::new(),
+                result_hash: String::new(),
+                matches_original: false,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+
+    /// Accepts a JSON `PixelExecuteRequest`, or with `Content-Type: application/octet-stream`
+    /// the program as packed RGBA bytes and the remaining settings as query parameters.
+    /// `?region=x,y,w,h` trims the returned canvas to that rectangle.
     pub async fn execute_pixel_program(
         State(runtime): State<Arc<AiRuntime>>,
-        Json(request): Json<PixelExecuteRequest>,
-    ) -> Json<PixelProgramResponse> {
-        let pixel_request = PixelProgramRequest {
-            program: request.program,
-            backend: request.backend,
-            max_cycles: request.max_cycles,
-            canvas_width: request.canvas_width,
-            canvas_height: request.canvas_height,
+        headers: HeaderMap,
+        Query(params): Query<PixelRunParams>,
+        body: Bytes,
+    ) -> Response {
+        let packed = headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with(OCTET_STREAM));
+        let region = match params.region.as_deref().map(str::parse::<CanvasRegion>) {
+            None => None,
+            Some(Ok(region)) => Some(region),
+            Some(Err(message)) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(PixelProgramResponse::error(message)),
+                )
+                    .into_response()
+            }
         };
+        let decoded = if packed {
+            decode_packed_program(&body).map(|program| params.with_program(program))
+        } else {
+            serde_json::from_slice::<PixelExecuteRequest>(&body)
+                .map_err(|e| format!("invalid pixel program request: {}", e))
+        };
+        let resolved = decoded.and_then(|request| {
+            let (canvas_width, canvas_height, max_cycles) = runtime.pixel_limits().resolve(
+                request.canvas_width,
+                request.canvas_height,
+                request.max_cycles,
+            )?;
+            Ok(PixelProgramRequest {
+                program: request.program,
+                backend: request.backend,
+                max_cycles,
+                canvas_width,
+                canvas_height,
+            })
+        });
+        let pixel_request = match resolved {
+            Ok(request) => request,
+            Err(message) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(PixelProgramResponse::error(message)),
+                )
+                    .into_response()
+            }
+        };
+
+        let (canvas_width, canvas_height) =
+            (pixel_request.canvas_width, pixel_request.canvas_height);
 
-        match runtime.execute_pixel_program(pixel_request).await {
-            Ok(response) => Json(response),
-            Err(e) => Json(PixelProgramResponse::error(e.to_string())),
+        // axum drops this future when the client disconnects; the guard then cancels the run
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let _cancel_on_disconnect = cancel.clone().drop_guard();
+
+        match runtime
+            .execute_pixel_program_cancellable(pixel_request, cancel)
+            .await
+        {
+            Ok(mut response) => {
+                let mut headers = HeaderMap::new();
+                if let Some(region) = region {
+                    let region = region.clamp(canvas_width, canvas_height);
+                    response.canvas_data = crate::canvas_region(
+                        &response.canvas_data,
+                        canvas_width,
+                        canvas_height,
+                        region,
+                    );
+                    headers.insert(
+                        crate::CANVAS_REGION_HEADER,
+                        HeaderValue::from_str(&region.to_string()).unwrap(),
+                    );
+                }
+                let fingerprint =
+                    format!("{:016x}", crate::canvas_fingerprint(&response.canvas_data));
+                headers.insert(
+                    crate::CANVAS_FINGERPRINT_HEADER,
+                    HeaderValue::from_str(&fingerprint).unwrap(),
+                );
+                (headers, Json(response)).into_response()
+            }
+            Err(e @ AiRuntimeError::GpuUnavailable(_)) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(PixelProgramResponse::error(e.to_string())),
+            )
+                .into_response(),
+            Err(e @ AiRuntimeError::GpuBusy(_)) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(PixelProgramResponse::error(e.to_string())),
+            )
+                .into_response(),
+            Err(e) => Json(PixelProgramResponse::error(e.to_string())).into_response(),
         }
     }
 
@@ -166,6 +347,75 @@ impl ApiServer {
         })
     }
 
+    /// Executed steps of recent pixel programs for every opcode, keyed by mnemonic, so opcodes
+    /// that never ran show up as 0
+    pub async fn pixel_opcode_coverage(
+        State(runtime): State<Arc<AiRuntime>>,
+    ) -> Json<CoverageResponse> {
+        let opcodes = runtime
+            .opcode_coverage()
+            .into_iter()
+            .map(|(op, count)| (crate::pixel_vm::op_mnemonic(op).to_string(), count))
+            .collect();
+        Json(CoverageResponse {
+            opcodes,
+            unattributed_steps: runtime.opcode_counts().unattributed_steps,
+        })
+    }
+
+    pub async fn diff_pixel_backends(
+        State(runtime): State<Arc<AiRuntime>>,
+        Json(request): Json<PixelDiffRequest>,
+    ) -> Response {
+        let (canvas_width, canvas_height, max_cycles) = match runtime.pixel_limits().resolve_diff(
+            request.canvas_width,
+            request.canvas_height,
+            request.max_cycles,
+        ) {
+            Ok(resolved) => resolved,
+            Err(message) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ErrorResponse {
+                        success: false,
+                        error: message,
+                    }),
+                )
+                    .into_response()
+            }
+        };
+        // As in `run_pixel_program`: a client disconnect drops this future and cancels the replays
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let _cancel_on_disconnect = cancel.clone().drop_guard();
+        match runtime
+            .diff_backends(
+                request.program,
+                canvas_width,
+                canvas_height,
+                max_cycles,
+                cancel,
+            )
+            .await
+        {
+            Ok(diff) => Json(diff).into_response(),
+            Err(e) => {
+                let status = match e {
+                    AiRuntimeError::GpuUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                    AiRuntimeError::GpuBusy(_) => StatusCode::TOO_MANY_REQUESTS,
+                    _ => StatusCode::INTERNAL_SERVER_ERROR,
+                };
+                (
+                    status,
+                    Json(ErrorResponse {
+                        success: false,
+                        error: e.to_string(),
+                    }),
+                )
+                    .into_response()
+            }
+        }
+    }
+
     pub async fn create_cartridge(
         State(runtime): State<Arc<AiRuntime>>,
         Json(payload): Json<CreateCartridgeRequest>,
@@ -177,6 +427,7 @@ impl ApiServer {
             name: payload.name,
             description: payload.description,
             code: payload.code,
+            code_format: payload.code_format,
             version: "1.0.0".to_string(),
             author: Some("API".to_string()),
             tags: vec![],
@@ -213,6 +464,7 @@ impl ApiServer {
             name: payload.name,
             description: payload.description,
             code: payload.code,
+            code_format: payload.code_format,
             version: payload.version.unwrap_or("1.0.0".to_string()),
             author: payload.author,
             tags: payload.tags.unwrap_or_default(),
@@ -280,17 +532,180 @@ pub struct ExecuteResponse {
     output: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    pub index: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReplayResponse {
+    pub success: bool,
+    pub output: String,
+    pub result_hash: String,
+    pub matches_original: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PixelExecuteRequest {
     pub program: Vec<PixelInstruction>,
     #[serde(default)]
     pub backend: ExecutionBackend,
-    #[serde(default = "default_max_cycles")]
-    pub max_cycles: u64,
-    #[serde(default = "default_canvas_width")]
-    pub canvas_width: u32,
-    #[serde(default = "default_canvas_height")]
-    pub canvas_height: u32,
+    #[serde(default)]
+    pub max_cycles: Option<u64>,
+    #[serde(default)]
+    pub canvas_width: Option<u32>,
+    #[serde(default)]
+    pub canvas_height: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PixelDiffRequest {
+    pub program: Vec<PixelInstruction>,
+    #[serde(default)]
+    pub max_cycles: Option<u64>,
+    #[serde(default)]
+    pub canvas_width: Option<u32>,
+    #[serde(default)]
+    pub canvas_height: Option<u32>,
+}
+
+/// Settings for a packed-bytes `/api/pixel/run` request, with the same defaults as the JSON body
+#[derive(Debug, Deserialize)]
+pub struct PixelRunParams {
+    #[serde(default)]
+    pub backend: ExecutionBackend,
+    #[serde(default)]
+    pub max_cycles: Option<u64>,
+    #[serde(default)]
+    pub canvas_width: Option<u32>,
+    #[serde(default)]
+    pub canvas_height: Option<u32>,
+    /// `x,y,w,h`: return only this part of the canvas, clamped to its bounds
+    #[serde(default)]
+    pub region: Option<String>,
+}
+
+impl PixelRunParams {
+    fn with_program(self, program: Vec<PixelInstruction>) -> PixelExecuteRequest {
+        PixelExecuteRequest {
+            program,
+            backend: self.backend,
+            max_cycles: self.max_cycles,
+            canvas_width: self.canvas_width,
+            canvas_height: self.canvas_height,
+        }
+    }
+}
+
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// Fail any request whose handler hasn't produced a response within `timeout`
+/// with 408, so a stalled GPU dispatch or database lock can't hold a connection forever.
+pub fn with_request_timeout(router: Router, timeout: Duration) -> Router {
+    router.layer(
+        ServiceBuilder::new()
+            .layer(HandleErrorLayer::new(handle_timeout_error))
+            .timeout(timeout),
+    )
+}
+
+async fn handle_timeout_error(err: BoxError) -> (StatusCode, Json<ErrorResponse>) {
+    let status = if err.is::<tower::timeout::error::Elapsed>() {
+        StatusCode::REQUEST_TIMEOUT
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (
+        status,
+        Json(ErrorResponse {
+            success: false,
+            error: err.to_string(),
+        }),
+    )
+}
+
+fn text_markdown() -> Mime {
+    "text/markdown".parse().unwrap()
+}
+
+/// Pick the entry of `supported` the client's `Accept` header rates highest.
+///
+/// Ranges are weighted by their `q` value, with the most specific matching
+/// range deciding each type's weight; ties go to the earlier entry in
+/// `supported`. Falls back to JSON when the header is missing or accepts none
+/// of them.
+pub fn negotiate(headers: &HeaderMap, supported: &[Mime]) -> Mime {
+    let ranges: Vec<(Mime, f32)> = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|range| range.trim().parse::<Mime>().ok())
+        .map(|range| {
+            let q = range
+                .get_param("q")
+                .and_then(|q| q.as_str().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (range, q)
+        })
+        .collect();
+
+    let weight = |candidate: &Mime| -> f32 {
+        ranges
+            .iter()
+            .filter_map(|(range, q)| {
+                let specificity = if range.type_() == mime::STAR {
+                    0
+                } else if range.type_() != candidate.type_() {
+                    return None;
+                } else if range.subtype() == mime::STAR {
+                    1
+                } else if range.subtype() == candidate.subtype() {
+                    2
+                } else {
+                    return None;
+                };
+                Some((specificity, *q))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0.0, |(_, q)| q)
+    };
+
+    let mut best: Option<(&Mime, f32)> = None;
+    for candidate in supported {
+        let q = weight(candidate);
+        if q > 0.0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((candidate, q));
+        }
+    }
+    best.map_or(mime::APPLICATION_JSON, |(candidate, _)| candidate.clone())
+}
+
+/// One instruction per 4 bytes, in r, g, b, a order.
+fn decode_packed_program(bytes: &[u8]) -> Result<Vec<PixelInstruction>, String> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(format!(
+            "packed program is {} bytes, not a multiple of 4",
+            bytes.len()
+        ));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|rgba| PixelInstruction::new(rgba[0], rgba[1], rgba[2], rgba[3]))
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatsQuery {
+    #[serde(default = "default_stats_hours")]
+    pub hours: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -311,12 +726,21 @@ pub struct BackendsResponse {
     pub backends: Vec<String>,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CoverageResponse {
+    pub opcodes: BTreeMap<String, u64>,
+    /// Steps of runs that jumped, which the executor can't attribute to opcodes
+    pub unattributed_steps: u64,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateCartridgeRequest {
     pub id: String,
     pub name: String,
     pub description: String,
     pub code: String,
+    #[serde(default)]
+    pub code_format: CodeFormat,
 }
 
 #[derive(Debug, Serialize)]
@@ -338,16 +762,8 @@ pub struct ErrorResponse {
     pub error: String,
 }
 
-fn default_max_cycles() -> u64 {
-    1024
-}
-
-fn default_canvas_width() -> u32 {
-    64
-}
-
-fn default_canvas_height() -> u32 {
-    64
+fn default_stats_hours() -> i64 {
+    24
 }
 
 #[derive(Debug, Deserialize)]
@@ -355,6 +771,8 @@ pub struct UpdateCartridgeRequest {
     pub name: String,
     pub description: String,
     pub code: String,
+    #[serde(default)]
+    pub code_format: CodeFormat,
     pub version: Option<String>,
     pub author: Option<String>,
     pub tags: Option<Vec<String>>,
@@ -362,14 +780,20 @@ pub struct UpdateCartridgeRequest {
 
 // GVPIe Analysis API handlers
 impl ApiServer {
-    /// Analyze the entire GVPIe codebase
+    /// Analyze the entire GVPIe codebase, optionally filtered by `min_priority`/`min_severity`
     async fn analyze_gvpie_codebase(
         State(runtime): State<Arc<AiRuntime>>,
-    ) -> Result<Json<crate::gvpie_analysis::GvpieAnalysisReport>, (axum::http::StatusCode, String)>
-    {
-        match runtime.analyze_gvpie_codebase().await {
-            Ok(report) => Ok(Json(report)),
-            Err(e) => Err((axum::http::StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+        Query(filter): Query<AnalysisFilter>,
+    ) -> Response {
+        let deadline = std::time::Duration::from_millis(runtime.config().api.analysis_timeout_ms);
+        match runtime.analyze_gvpie_codebase_within(deadline).await {
+            Ok(crate::gvpie_analysis::BoundedAnalysis {
+                report: Some(report),
+                ..
+            }) => Json(report.filtered(filter.min_priority, filter.min_severity)).into_response(),
+            // Deadline passed: 504 with whatever the analysis emitted, marked truncated
+            Ok(bounded) => (StatusCode::GATEWAY_TIMEOUT, Json(bounded)).into_response(),
+            Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
         }
     }
 
@@ -404,6 +828,27 @@ impl ApiServer {
         }
     }
 
+    /// Export optimization suggestions as review comments
+    async fn get_review_comments(
+        State(runtime): State<Arc<AiRuntime>>,
+        headers: HeaderMap,
+    ) -> Response {
+        let comments = match runtime.analyze_gvpie_codebase().await {
+            Ok(report) => report.to_review_comments(),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        };
+        if negotiate(&headers, &[mime::APPLICATION_JSON, text_markdown()]) == text_markdown() {
+            let markdown = crate::gvpie_analysis::review_comments_to_markdown(&comments);
+            (
+                [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+                markdown,
+            )
+                .into_response()
+        } else {
+            Json(comments).into_response()
+        }
+    }
+
     /// Get comprehensive development assistance
     async fn get_development_assistance(
         State(runtime): State<Arc<AiRuntime>>,
@@ -430,6 +875,12 @@ impl ApiServer {
     }
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct AnalysisFilter {
+    pub min_priority: Option<crate::gvpie_analysis::Priority>,
+    pub min_severity: Option<crate::gvpie_analysis::SecuritySeverity>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GvpieSuggestionsRequest {
     pub changed_files: Vec<String>,
@@ -439,3 +890,54 @@ pub struct GvpieSuggestionsRequest {
 pub struct PerformancePredictionRequest {
     pub changes: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accept(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn negotiate_picks_requested_markdown() {
+        let supported = [mime::APPLICATION_JSON, text_markdown(), mime::IMAGE_PNG];
+        assert_eq!(
+            negotiate(&accept("text/markdown"), &supported),
+            text_markdown()
+        );
+        assert_eq!(
+            negotiate(&accept("text/*;q=0.9, image/png;q=0.5"), &supported),
+            text_markdown()
+        );
+    }
+
+    #[test]
+    fn negotiate_prefers_higher_q_over_wildcard() {
+        let supported = [text_markdown(), mime::APPLICATION_JSON];
+        assert_eq!(
+            negotiate(&accept("application/json, */*;q=0.1"), &supported),
+            mime::APPLICATION_JSON
+        );
+        assert_eq!(negotiate(&accept("*/*"), &supported), text_markdown());
+    }
+
+    #[test]
+    fn negotiate_falls_back_to_json() {
+        let supported = [text_markdown(), mime::IMAGE_PNG];
+        assert_eq!(
+            negotiate(&accept("image/gif"), &supported),
+            mime::APPLICATION_JSON
+        );
+        assert_eq!(
+            negotiate(&accept("text/markdown;q=0"), &supported),
+            mime::APPLICATION_JSON
+        );
+        assert_eq!(
+            negotiate(&HeaderMap::new(), &supported),
+            mime::APPLICATION_JSON
+        );
+    }
+}
@@ -6,200 +6,606 @@
 //!   cargo run --bin gvpie_dev_assistant analyze
 //!   cargo run --bin gvpie_dev_assistant suggest --files src/gpu/mod.rs
 //!   cargo run --bin gvpie_dev_assistant assist
+//!   cargo run --bin gvpie_dev_assistant trends cpu 24
+//!   cargo run --bin gvpie_dev_assistant patterns 100
+//!
+//!   cargo run --bin gvpie_dev_assistant analyze --format json
+//!   cargo run --bin gvpie_dev_assistant watch src/
+//!   cargo run --bin gvpie_dev_assistant selftest
+//!
+//! `trends` and `patterns` read the same experience database as the runtime:
+//! `GVPIE_DB_PATH`, else the configured `database_url`. `--format json` prints the underlying
+//! report structs instead of the human-readable summary. `selftest` exits 0 on
+//! pass, 1 on failure and 2 when no GPU was available to test.
 
-use ai_runtime::AiRuntime;
-use std::path::PathBuf;
+use ai_runtime::selftest::CheckStatus;
+use ai_runtime::{AiRuntime, ExperienceDB, OptimizationSuggestion, SelftestOutcome};
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DEFAULT_TREND_HOURS: i64 = 24;
+const DEFAULT_PATTERN_WINDOW: usize = 100;
+/// Quiet period after the last file event before `watch` re-analyzes.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
 
 #[derive(Debug)]
 enum Command {
-    Analyze,
-    Suggest { files: Vec<String> },
+    /// `stream` prints each suggestion/finding as a JSON line while analyzing
+    Analyze {
+        stream: bool,
+    },
+    Suggest {
+        files: Vec<String>,
+    },
     Assist,
-    Component { path: String },
-    Predict { changes: Vec<String> },
+    Component {
+        path: String,
+    },
+    Predict {
+        changes: Vec<String>,
+    },
+    Trends {
+        metric: String,
+        hours: i64,
+    },
+    Patterns {
+        window: usize,
+    },
+    Watch {
+        dir: String,
+    },
+    Selftest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
+    // Initialize logging (on stderr, so stdout stays machine-readable)
     tracing_subscriber::fmt()
         .with_target(false)
         .with_level(false)
+        .with_writer(std::io::stderr)
         .init();
 
     let args: Vec<String> = std::env::args().collect();
+    let (format, args) = split_format_flag(&args)?;
     let command = parse_args(&args)?;
 
-    println!("🤖 GVPIe AI Development Assistant");
-    println!("==================================");
+    match command {
+        Command::Analyze { stream: true } => stream_analysis().await,
+        Command::Analyze { stream: false } => analyze(format).await,
+        Command::Suggest { files } => suggest(files, format).await,
+        Command::Assist => assist(format).await,
+        Command::Component { path } => component(&path, format).await,
+        Command::Predict { changes } => predict(&changes, format).await,
+        Command::Trends { metric, hours } => show_trends(&metric, hours, format).await,
+        Command::Patterns { window } => show_patterns(window, format).await,
+        Command::Watch { dir } => watch(PathBuf::from(dir), format).await,
+        Command::Selftest => selftest(format).await,
+    }
+}
 
-    // Initialize AI Runtime
+/// Analyze the whole codebase: scores, top optimizations and security findings
+async fn analyze(format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    if format == OutputFormat::Json {
+        let runtime = AiRuntime::new().await?;
+        return print_json(&runtime.analyze_gvpie_codebase().await?);
+    }
+
+    print_banner();
     let runtime = AiRuntime::new().await?;
+    println!("📊 Analyzing entire GVPIe codebase...");
+    let report = runtime.analyze_gvpie_codebase().await?;
 
-    match command {
-        Command::Analyze => {
-            println!("📊 Analyzing entire GVPIe codebase...");
-            let report = runtime.analyze_gvpie_codebase().await?;
+    println!("\n✅ Analysis Complete!");
+    println!("📈 Scores:");
+    println!(
+        "  • Architecture: {:.1}%",
+        report.architecture_analysis.modularity_score * 100.0
+    );
+    println!(
+        "  • GPU Utilization: {:.1}%",
+        report.gpu_analysis.gpu_utilization_score * 100.0
+    );
+    println!(
+        "  • Pixel VM: {:.1}%",
+        report.pixel_vm_analysis.vm_performance_score * 100.0
+    );
 
-            println!("\n✅ Analysis Complete!");
-            println!("📈 Scores:");
-            println!(
-                "  • Architecture: {:.1}%",
-                report.architecture_analysis.modularity_score * 100.0
-            );
-            println!(
-                "  • GPU Utilization: {:.1}%",
-                report.gpu_analysis.gpu_utilization_score * 100.0
-            );
+    println!("\n🔧 Top Optimizations:");
+    for (i, suggestion) in report.optimization_suggestions.iter().take(5).enumerate() {
+        println!(
+            "  {}. [{}] {}",
+            i + 1,
+            format!("{:?}", suggestion.priority),
+            suggestion.description
+        );
+    }
+
+    if !report.security_findings.is_empty() {
+        println!("\n🛡️  Security Findings:");
+        for finding in report.security_findings.iter().take(3) {
             println!(
-                "  • Pixel VM: {:.1}%",
-                report.pixel_vm_analysis.vm_performance_score * 100.0
+                "  • [{}] {}",
+                format!("{:?}", finding.severity),
+                finding.description
             );
+        }
+    }
 
-            println!("\n🔧 Top Optimizations:");
-            for (i, suggestion) in report.optimization_suggestions.iter().take(5).enumerate() {
-                println!(
-                    "  {}. [{}] {}",
-                    i + 1,
-                    format!("{:?}", suggestion.priority),
-                    suggestion.description
-                );
-            }
+    print_footer();
+    Ok(())
+}
 
-            if !report.security_findings.is_empty() {
-                println!("\n🛡️  Security Findings:");
-                for finding in report.security_findings.iter().take(3) {
-                    println!(
-                        "  • [{}] {}",
-                        format!("{:?}", finding.severity),
-                        finding.description
-                    );
-                }
+/// Suggest improvements for the given changed files
+async fn suggest(
+    files: Vec<String>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let paths: Vec<PathBuf> = files.iter().map(PathBuf::from).collect();
+    if format == OutputFormat::Json {
+        let runtime = AiRuntime::new().await?;
+        return print_json(&runtime.suggest_gvpie_improvements(&paths).await?);
+    }
+
+    print_banner();
+    let runtime = AiRuntime::new().await?;
+    println!("💡 Getting suggestions for {} files...", files.len());
+    let suggestions = runtime.suggest_gvpie_improvements(&paths).await?;
+
+    println!("\n✅ Generated {} suggestions:", suggestions.len());
+    for (i, suggestion) in suggestions.iter().enumerate() {
+        println!(
+            "  {}. [{}] {}",
+            i + 1,
+            format!("{:?}", suggestion.priority),
+            suggestion.description
+        );
+
+        if let Some(code) = &suggestion.suggested_code {
+            println!("     💻 Suggested code:");
+            for line in code.lines().take(2) {
+                println!("        {}", line.trim());
+            }
+            if code.lines().count() > 2 {
+                println!("        ...");
             }
         }
+    }
 
-        Command::Suggest { files } => {
-            println!("💡 Getting suggestions for {} files...", files.len());
-            let paths: Vec<PathBuf> = files.into_iter().map(PathBuf::from).collect();
-            let suggestions = runtime.suggest_gvpie_improvements(&paths).await?;
+    print_footer();
+    Ok(())
+}
 
-            println!("\n✅ Generated {} suggestions:", suggestions.len());
-            for (i, suggestion) in suggestions.iter().enumerate() {
-                println!(
-                    "  {}. [{}] {}",
-                    i + 1,
-                    format!("{:?}", suggestion.priority),
-                    suggestion.description
-                );
+/// Development recommendations and next actions
+async fn assist(format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    if format == OutputFormat::Json {
+        let runtime = AiRuntime::new().await?;
+        return print_json(&runtime.get_gvpie_development_assistance().await?);
+    }
 
-                if let Some(code) = &suggestion.suggested_code {
-                    println!("     💻 Suggested code:");
-                    for line in code.lines().take(2) {
-                        println!("        {}", line.trim());
-                    }
-                    if code.lines().count() > 2 {
-                        println!("        ...");
-                    }
-                }
-            }
+    print_banner();
+    let runtime = AiRuntime::new().await?;
+    println!("🧠 Getting comprehensive development assistance...");
+    let assistance = runtime.get_gvpie_development_assistance().await?;
+
+    println!("\n📋 Development Recommendations:");
+    for (i, rec) in assistance.recommendations.iter().enumerate() {
+        println!("  {}. [{}] {}", i + 1, rec.priority, rec.title);
+        println!("     📖 {}", rec.description);
+        println!(
+            "     ⏱️  {} | 🎯 {}",
+            rec.estimated_effort, rec.expected_impact
+        );
+    }
+
+    println!("\n🎯 Next Actions:");
+    for (i, action) in assistance.next_actions.iter().enumerate() {
+        println!(
+            "  {}. {} ({})",
+            i + 1,
+            action.description,
+            action.estimated_time
+        );
+        if let Some(command) = &action.command {
+            println!("     💻 {}", command);
         }
+    }
 
-        Command::Assist => {
-            println!("🧠 Getting comprehensive development assistance...");
-            let assistance = runtime.get_gvpie_development_assistance().await?;
+    print_footer();
+    Ok(())
+}
 
-            println!("\n📋 Development Recommendations:");
-            for (i, rec) in assistance.recommendations.iter().enumerate() {
-                println!("  {}. [{}] {}", i + 1, rec.priority, rec.title);
-                println!("     📖 {}", rec.description);
-                println!(
-                    "     ⏱️  {} | 🎯 {}",
-                    rec.estimated_effort, rec.expected_impact
-                );
+/// Analyze a single component or file
+async fn component(path: &str, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    if format == OutputFormat::Json {
+        let runtime = AiRuntime::new().await?;
+        return print_json(&runtime.analyze_gvpie_component(path).await?);
+    }
+
+    print_banner();
+    let runtime = AiRuntime::new().await?;
+    println!("🔍 Analyzing component: {}", path);
+    let report = runtime.analyze_gvpie_component(path).await?;
+
+    println!("\n✅ Component Analysis Complete!");
+    println!("📊 Component Scores:");
+    println!(
+        "  • Architecture: {:.1}%",
+        report.architecture_analysis.modularity_score * 100.0
+    );
+    println!(
+        "  • GPU Performance: {:.1}%",
+        report.gpu_analysis.compute_shader_efficiency * 100.0
+    );
+
+    if !report.optimization_suggestions.is_empty() {
+        println!("\n🔧 Component-Specific Optimizations:");
+        for (i, suggestion) in report.optimization_suggestions.iter().take(3).enumerate() {
+            println!("  {}. {}", i + 1, suggestion.description);
+        }
+    }
+
+    print_footer();
+    Ok(())
+}
+
+/// Predict the performance impact of the described changes
+async fn predict(
+    changes: &[String],
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if format == OutputFormat::Json {
+        let runtime = AiRuntime::new().await?;
+        return print_json(&runtime.predict_gvpie_performance_impact(changes).await?);
+    }
+
+    print_banner();
+    let runtime = AiRuntime::new().await?;
+    println!(
+        "⚡ Predicting performance impact of {} changes...",
+        changes.len()
+    );
+    let insights = runtime.predict_gvpie_performance_impact(changes).await?;
+
+    println!("\n📈 Performance Predictions:");
+    println!(
+        "  • GPU/CPU Balance: {:.1}%",
+        insights.gpu_cpu_balance * 100.0
+    );
+    println!(
+        "  • Scaling (1K users): {:.1}%",
+        insights.predicted_scalability.predicted_1k_users
+    );
+    println!(
+        "  • Scaling (10K users): {:.1}%",
+        insights.predicted_scalability.predicted_10k_users
+    );
+
+    if !insights
+        .predicted_scalability
+        .scaling_bottlenecks
+        .is_empty()
+    {
+        println!("\n🚨 Potential Bottlenecks:");
+        for bottleneck in &insights.predicted_scalability.scaling_bottlenecks {
+            println!("  • {}", bottleneck);
+        }
+    }
+
+    print_footer();
+    Ok(())
+}
+
+fn print_banner() {
+    println!("🤖 GVPIe AI Development Assistant");
+    println!("==================================");
+}
+
+fn print_footer() {
+    println!("\n🎉 Analysis complete! Use the insights to accelerate your GVPIe development.");
+}
+
+/// Exercise every pixel backend and print a pass/fail report, exiting nonzero unless all passed.
+async fn selftest(format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = AiRuntime::new().await?;
+    let report = runtime.selftest().await;
+
+    if format == OutputFormat::Json {
+        println!("{}", to_json(&report)?);
+    } else {
+        println!("🩺 GVPIe selftest (ai-runtime {})", report.version);
+        println!("  • Backends: {}", report.backends.join(", "));
+        match report.gpu_memory.budget_bytes {
+            Some(budget) => println!("  • GPU memory budget: {} MiB", budget / 1024 / 1024),
+            None => println!("  • GPU memory budget: unknown"),
+        }
+        match &report.adapter {
+            Some(adapter) => println!("  • Adapter: {}", adapter),
+            None => println!("  • Adapter: not exposed by gvpie-core"),
+        }
+        for check in &report.checks {
+            let mark = match check.status {
+                CheckStatus::Pass => "✅",
+                CheckStatus::Fail => "❌",
+                CheckStatus::Skipped => "⏭️",
+            };
+            println!("  {} {}: {}", mark, check.name, check.detail);
+        }
+        println!(
+            "\n{}",
+            match report.outcome {
+                SelftestOutcome::Pass => "PASS",
+                SelftestOutcome::CpuOnly => "CPU-only: GPU pipeline not exercised",
+                SelftestOutcome::Fail => "FAIL",
             }
+        );
+    }
 
-            println!("\n🎯 Next Actions:");
-            for (i, action) in assistance.next_actions.iter().enumerate() {
-                println!(
-                    "  {}. {} ({})",
-                    i + 1,
-                    action.description,
-                    action.estimated_time
-                );
-                if let Some(command) = &action.command {
-                    println!("     💻 {}", command);
-                }
+    match report.exit_code() {
+        0 => Ok(()),
+        code => std::process::exit(code),
+    }
+}
+
+/// Run a full analysis, printing each suggestion and finding as a JSON line as it arrives.
+async fn stream_analysis() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+
+    let runtime = AiRuntime::new().await?;
+    let mut write_error = None;
+    runtime
+        .analyze_gvpie_codebase_streaming(|item| {
+            if write_error.is_some() {
+                return;
             }
+            let mut stdout = std::io::stdout().lock();
+            let line = serde_json::to_string(&item).map_err(std::io::Error::from);
+            if let Err(e) = line.and_then(|line| {
+                writeln!(stdout, "{}", line)?;
+                stdout.flush()
+            }) {
+                write_error = Some(e);
+            }
+        })
+        .await?;
+    match write_error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", to_json(value)?);
+    Ok(())
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+/// Coalesces bursts of file events into one analysis once events go quiet.
+struct Debouncer {
+    quiet: Duration,
+    pending: BTreeSet<PathBuf>,
+    last_event: Option<Instant>,
+}
+
+impl Debouncer {
+    fn new(quiet: Duration) -> Self {
+        Self {
+            quiet,
+            pending: BTreeSet::new(),
+            last_event: None,
         }
+    }
 
-        Command::Component { path } => {
-            println!("🔍 Analyzing component: {}", path);
-            let report = runtime.analyze_gvpie_component(&path).await?;
+    fn push(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path);
+        self.last_event = Some(now);
+    }
 
-            println!("\n✅ Component Analysis Complete!");
-            println!("📊 Component Scores:");
-            println!(
-                "  • Architecture: {:.1}%",
-                report.architecture_analysis.modularity_score * 100.0
-            );
-            println!(
-                "  • GPU Performance: {:.1}%",
-                report.gpu_analysis.compute_shader_efficiency * 100.0
-            );
+    /// The changed files, once `quiet` has passed since the last event.
+    fn ready(&mut self, now: Instant) -> Option<Vec<PathBuf>> {
+        let last = self.last_event?;
+        if now.duration_since(last) < self.quiet {
+            return None;
+        }
+        self.last_event = None;
+        Some(std::mem::take(&mut self.pending).into_iter().collect())
+    }
+}
+
+fn is_watched_source(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("rs") | Some("wgsl")
+    )
+}
+
+fn suggestion_key(suggestion: &OptimizationSuggestion) -> String {
+    format!(
+        "{}\n{}",
+        suggestion.description,
+        suggestion.suggested_code.as_deref().unwrap_or_default()
+    )
+}
+
+/// Re-run `suggest_gvpie_improvements` whenever `.rs`/`.wgsl` files under
+/// `dir` change, printing only suggestions not reported by the previous run
+/// for the same file. A failed analysis is logged and the watch continues.
+async fn watch(dir: PathBuf, format: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = AiRuntime::new().await?;
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let _ = tx.send(event);
+    })?;
+    watcher.watch(&dir, RecursiveMode::Recursive)?;
+
+    if format == OutputFormat::Text {
+        println!(
+            "👀 Watching {} for .rs/.wgsl changes (Ctrl+C to stop)...",
+            dir.display()
+        );
+    }
 
-            if !report.optimization_suggestions.is_empty() {
-                println!("\n🔧 Component-Specific Optimizations:");
-                for (i, suggestion) in report.optimization_suggestions.iter().take(3).enumerate() {
-                    println!("  {}. {}", i + 1, suggestion.description);
+    let mut debouncer = Debouncer::new(WATCH_DEBOUNCE);
+    let mut previous: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    loop {
+        match tokio::time::timeout(WATCH_DEBOUNCE, rx.recv()).await {
+            Ok(Some(Ok(event))) => {
+                for path in event.paths.into_iter().filter(|p| is_watched_source(p)) {
+                    debouncer.push(path, Instant::now());
                 }
             }
+            Ok(Some(Err(e))) => tracing::warn!("watch error: {}", e),
+            Ok(None) => break,
+            Err(_) => {}
         }
 
-        Command::Predict { changes } => {
-            println!(
-                "⚡ Predicting performance impact of {} changes...",
-                changes.len()
-            );
-            let insights = runtime.predict_gvpie_performance_impact(&changes).await?;
-
-            println!("\n📈 Performance Predictions:");
-            println!(
-                "  • GPU/CPU Balance: {:.1}%",
-                insights.gpu_cpu_balance * 100.0
-            );
-            println!(
-                "  • Scaling (1K users): {:.1}%",
-                insights.predicted_scalability.predicted_1k_users
-            );
-            println!(
-                "  • Scaling (10K users): {:.1}%",
-                insights.predicted_scalability.predicted_10k_users
+        let Some(changed) = debouncer.ready(Instant::now()) else {
+            continue;
+        };
+        let mut fresh: Vec<OptimizationSuggestion> = Vec::new();
+        for path in &changed {
+            let suggestions = match runtime
+                .suggest_gvpie_improvements(std::slice::from_ref(path))
+                .await
+            {
+                Ok(suggestions) => suggestions,
+                Err(e) => {
+                    tracing::warn!("analysis of {} failed: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let seen = previous.entry(path.clone()).or_default();
+            let current: HashSet<String> = suggestions.iter().map(suggestion_key).collect();
+            fresh.extend(
+                suggestions
+                    .into_iter()
+                    .filter(|s| !seen.contains(&suggestion_key(s))),
             );
+            *seen = current;
+        }
 
-            if !insights
-                .predicted_scalability
-                .scaling_bottlenecks
-                .is_empty()
-            {
-                println!("\n🚨 Potential Bottlenecks:");
-                for bottleneck in &insights.predicted_scalability.scaling_bottlenecks {
-                    println!("  • {}", bottleneck);
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string(&fresh)?),
+            OutputFormat::Text => {
+                println!(
+                    "\n🔄 {} file(s) changed, {} new suggestions",
+                    changed.len(),
+                    fresh.len()
+                );
+                for (i, suggestion) in fresh.iter().enumerate() {
+                    println!(
+                        "  {}. [{:?}] {}",
+                        i + 1,
+                        suggestion.priority,
+                        suggestion.description
+                    );
                 }
             }
         }
     }
+    Ok(())
+}
+
+async fn open_db() -> Result<ExperienceDB, Box<dyn std::error::Error>> {
+    let config = ai_runtime::config::Config::load()?;
+    Ok(ExperienceDB::with_config(config.database_path(), &config.database).await?)
+}
+
+async fn show_trends(
+    metric: &str,
+    hours: i64,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if format == OutputFormat::Json {
+        return print_json(&open_db().await?.analyze_trends(metric, hours).await?);
+    }
 
-    println!("\n🎉 Analysis complete! Use the insights to accelerate your GVPIe development.");
+    print_banner();
+    println!("📉 Trend for '{}' over the last {} hours...", metric, hours);
+    let db = open_db().await?;
+    let trend = db.analyze_trends(metric, hours).await?;
+
+    println!("\n📈 {}:", trend.metric);
+    println!("  • Current: {:.2}", trend.current);
+    println!("  • Direction: {}", trend.direction);
+    match trend.trend_percent {
+        Some(pct) => println!("  • Change: {:+.1}%", pct),
+        None => println!("  • Change: n/a (baseline was zero)"),
+    }
+    println!("  • Samples: {}", trend.samples);
     Ok(())
 }
 
+async fn show_patterns(
+    window: usize,
+    format: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if format == OutputFormat::Json {
+        return print_json(&open_db().await?.analyze_patterns(window).await?);
+    }
+
+    print_banner();
+    println!("🔎 Resource patterns over the last {} samples...", window);
+    let db = open_db().await?;
+    let patterns = db.analyze_patterns(window).await?;
+
+    println!("\n📊 Averages:");
+    println!("  • CPU: {:.1}%", patterns.resource_trends.cpu_avg);
+    println!("  • Memory: {:.1}%", patterns.resource_trends.memory_avg);
+    println!("  • Disk: {:.1}%", patterns.resource_trends.disk_avg);
+    Ok(())
+}
+
+/// Strip the global `--format json|text` flag (which may appear anywhere)
+/// and return it alongside the remaining arguments.
+fn split_format_flag(
+    args: &[String],
+) -> Result<(OutputFormat, Vec<String>), Box<dyn std::error::Error>> {
+    let mut format = OutputFormat::Text;
+    let mut rest = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = if arg == "--format" {
+            iter.next().ok_or("Usage: --format json|text")?.as_str()
+        } else if let Some(value) = arg.strip_prefix("--format=") {
+            value
+        } else {
+            rest.push(arg.clone());
+            continue;
+        };
+        format = match value {
+            "json" => OutputFormat::Json,
+            "text" => OutputFormat::Text,
+            other => return Err(format!("Unknown format: {}. Available: json, text", other).into()),
+        };
+    }
+    Ok((format, rest))
+}
+
 fn parse_args(args: &[String]) -> Result<Command, Box<dyn std::error::Error>> {
     if args.len() < 2 {
         return Err("Usage: gvpie_dev_assistant <command> [options]".into());
     }
 
     match args[1].as_str() {
-        "analyze" => Ok(Command::Analyze),
+        "analyze" => match args.get(2).map(String::as_str) {
+            None => Ok(Command::Analyze { stream: false }),
+            Some("--stream") => Ok(Command::Analyze { stream: true }),
+            Some(other) => Err(format!("Unknown analyze option: {}", other).into()),
+        },
         "suggest" => {
             let files = if args.len() > 3 && args[2] == "--files" {
                 args[3..].to_vec()
@@ -225,10 +631,154 @@ fn parse_args(args: &[String]) -> Result<Command, Box<dyn std::error::Error>> {
             };
             Ok(Command::Predict { changes })
         }
+        "trends" => {
+            if args.len() < 3 {
+                return Err("Usage: gvpie_dev_assistant trends <metric> [hours]".into());
+            }
+            let hours = match args.get(3) {
+                Some(hours) => hours
+                    .parse()
+                    .map_err(|_| format!("Invalid hours: {}", hours))?,
+                None => DEFAULT_TREND_HOURS,
+            };
+            Ok(Command::Trends {
+                metric: args[2].clone(),
+                hours,
+            })
+        }
+        "patterns" => {
+            let window = match args.get(2) {
+                Some(window) => window
+                    .parse()
+                    .map_err(|_| format!("Invalid window: {}", window))?,
+                None => DEFAULT_PATTERN_WINDOW,
+            };
+            Ok(Command::Patterns { window })
+        }
+        "watch" => {
+            if args.len() < 3 {
+                return Err("Usage: gvpie_dev_assistant watch <dir>".into());
+            }
+            Ok(Command::Watch {
+                dir: args[2].clone(),
+            })
+        }
+        "selftest" => Ok(Command::Selftest),
         _ => Err(format!(
-            "Unknown command: {}. Available: analyze, suggest, assist, component, predict",
+            "Unknown command: {}. Available: analyze, suggest, assist, component, predict, trends, patterns, watch, selftest",
             args[1]
         )
         .into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        std::iter::once("gvpie_dev_assistant")
+            .chain(values.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn parses_trends_with_metric_and_hours() {
+        match parse_args(&args(&["trends", "cpu", "6"])).unwrap() {
+            Command::Trends { metric, hours } => {
+                assert_eq!(metric, "cpu");
+                assert_eq!(hours, 6);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+        assert!(matches!(
+            parse_args(&args(&["trends", "memory"])).unwrap(),
+            Command::Trends {
+                hours: DEFAULT_TREND_HOURS,
+                ..
+            }
+        ));
+        assert!(parse_args(&args(&["trends"])).is_err());
+        assert!(parse_args(&args(&["trends", "cpu", "soon"])).is_err());
+    }
+
+    #[test]
+    fn format_flag_is_global() {
+        let (format, rest) = split_format_flag(&args(&["analyze", "--format", "json"])).unwrap();
+        assert_eq!(format, OutputFormat::Json);
+        assert!(matches!(
+            parse_args(&rest).unwrap(),
+            Command::Analyze { stream: false }
+        ));
+        assert!(matches!(
+            parse_args(&args(&["selftest"])).unwrap(),
+            Command::Selftest
+        ));
+
+        let (format, rest) = split_format_flag(&args(&["--format=text", "patterns", "5"])).unwrap();
+        assert_eq!(format, OutputFormat::Text);
+        assert!(matches!(
+            parse_args(&rest).unwrap(),
+            Command::Patterns { window: 5 }
+        ));
+
+        assert!(split_format_flag(&args(&["analyze", "--format", "yaml"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn analyze_json_round_trips_into_report() {
+        let analyzer = ai_runtime::GvpieAnalyzer::new(env!("CARGO_MANIFEST_DIR"));
+        let report = analyzer.analyze_gvpie_codebase().await.unwrap();
+
+        let json = to_json(&report).unwrap();
+        let parsed: ai_runtime::GvpieAnalysisReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            parsed.optimization_suggestions.len(),
+            report.optimization_suggestions.len()
+        );
+    }
+
+    #[test]
+    fn debounce_coalesces_a_burst_into_one_analysis() {
+        let mut debouncer = Debouncer::new(Duration::from_millis(300));
+        let start = Instant::now();
+        let mut analyses = Vec::new();
+
+        for (i, file) in ["a.rs", "b.wgsl", "a.rs", "a.rs"].iter().enumerate() {
+            let now = start + Duration::from_millis(50 * i as u64);
+            debouncer.push(PathBuf::from(file), now);
+            analyses.extend(debouncer.ready(now));
+        }
+        for ms in [200, 400, 500, 900] {
+            analyses.extend(debouncer.ready(start + Duration::from_millis(ms)));
+        }
+
+        assert_eq!(analyses.len(), 1);
+        assert_eq!(
+            analyses[0],
+            vec![PathBuf::from("a.rs"), PathBuf::from("b.wgsl")]
+        );
+    }
+
+    #[test]
+    fn watch_only_tracks_rust_and_wgsl() {
+        assert!(is_watched_source(Path::new("src/lib.rs")));
+        assert!(is_watched_source(Path::new("shaders/pixel.wgsl")));
+        assert!(!is_watched_source(Path::new("Cargo.toml")));
+    }
+
+    #[test]
+    fn parses_patterns_window() {
+        assert!(matches!(
+            parse_args(&args(&["patterns", "50"])).unwrap(),
+            Command::Patterns { window: 50 }
+        ));
+        assert!(matches!(
+            parse_args(&args(&["patterns"])).unwrap(),
+            Command::Patterns {
+                window: DEFAULT_PATTERN_WINDOW
+            }
+        ));
+    }
+}
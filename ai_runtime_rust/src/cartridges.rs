@@ -1,17 +1,45 @@
+use fs2::FileExt;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
+    io::ErrorKind,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
 };
 use thiserror::Error;
 
+/// Index of the cartridges on disk: id -> sha256 of the cartridge file.
+const INDEX_FILE: &str = "index.json";
+
+/// Advisory-locked by whichever manager is writing a cartridge file and its index entry.
+/// The file itself stays in place; only the OS lock on it is taken and released.
+const LOCK_FILE: &str = "index.lock";
+
+/// Distinguishes temp files written concurrently by the same process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// How a cartridge's `code` is interpreted.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeFormat {
+    /// Pixel assembly text, fed to the text assembler.
+    #[default]
+    PixelAsm,
+    /// Hex-encoded RGBA pixels, fed to the pixel assembler.
+    RawPixels,
+    /// Opaque bytes, passed through without assembly.
+    Binary,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Cartridge {
     pub id: String,
     pub name: String,
     pub description: String,
     pub code: String,
+    #[serde(default)]
+    pub code_format: CodeFormat,
     pub version: String,
     pub author: Option<String>,
     pub tags: Vec<String>,
@@ -25,11 +53,127 @@ pub enum CartridgeError {
     Io(#[from] std::io::Error),
     #[error("serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
+    #[error("invalid cartridge code: {0}")]
+    InvalidCode(String),
+    #[error("invalid cartridge id {0:?}: use letters, digits, '_' and '-'")]
+    InvalidId(String),
+}
+
+/// Ids name files under the storage root, so only `[A-Za-z0-9_-]` is allowed.
+fn validate_id(id: &str) -> Result<(), CartridgeError> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(CartridgeError::InvalidId(id.to_string()))
+    }
+}
+
+impl Cartridge {
+    /// Decode `code` as hex RGBA pixels (8 hex digits per pixel, whitespace ignored).
+    pub fn raw_pixels(&self) -> Result<Vec<[u8; 4]>, CartridgeError> {
+        let digits: Vec<u8> = self
+            .code
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .collect();
+        if !digits.len().is_multiple_of(8) {
+            return Err(CartridgeError::InvalidCode(format!(
+                "{}: raw pixel code must be a multiple of 8 hex digits, got {}",
+                self.id,
+                digits.len()
+            )));
+        }
+
+        digits
+            .chunks_exact(8)
+            .map(|pixel| {
+                let mut rgba = [0u8; 4];
+                for (channel, pair) in rgba.iter_mut().zip(pixel.chunks_exact(2)) {
+                    let hex = std::str::from_utf8(pair).unwrap_or_default();
+                    *channel = u8::from_str_radix(hex, 16).map_err(|_| {
+                        CartridgeError::InvalidCode(format!(
+                            "{}: invalid hex byte {:?}",
+                            self.id, hex
+                        ))
+                    })?;
+                }
+                Ok(rgba)
+            })
+            .collect()
+    }
+}
+
+/// Inverted index over cartridge names, descriptions and tags.
+#[derive(Debug, Default)]
+struct SearchIndex {
+    /// term -> cartridge id -> occurrences
+    postings: HashMap<String, HashMap<String, usize>>,
+    /// cartridge id -> its indexed terms, for removal
+    terms: HashMap<String, Vec<String>>,
+}
+
+impl SearchIndex {
+    fn insert(&mut self, cartridge: &Cartridge) {
+        self.remove(&cartridge.id);
+        let text = [&cartridge.name, &cartridge.description]
+            .into_iter()
+            .chain(&cartridge.tags)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(" ");
+        let terms = tokenize(&text);
+        for term in &terms {
+            *self
+                .postings
+                .entry(term.clone())
+                .or_default()
+                .entry(cartridge.id.clone())
+                .or_insert(0) += 1;
+        }
+        self.terms.insert(cartridge.id.clone(), terms);
+    }
+
+    fn remove(&mut self, id: &str) {
+        for term in self.terms.remove(id).unwrap_or_default() {
+            if let Some(ids) = self.postings.get_mut(&term) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    /// Ids matching any query term, most matches first (ties by id).
+    fn search(&self, query: &str) -> Vec<String> {
+        let mut scores: HashMap<&str, usize> = HashMap::new();
+        for term in tokenize(query) {
+            for (id, count) in self.postings.get(&term).into_iter().flatten() {
+                *scores.entry(id.as_str()).or_insert(0) += count;
+            }
+        }
+        let mut ranked: Vec<_> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranked.into_iter().map(|(id, _)| id.to_string()).collect()
+    }
+}
+
+/// Lowercased alphanumeric words.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
 }
 
 #[derive(Debug)]
 pub struct CartridgeManager {
     cartridges: HashMap<String, Cartridge>,
+    search_index: SearchIndex,
     storage_root: PathBuf,
 }
 
@@ -38,6 +182,7 @@ impl CartridgeManager {
         let storage_root = storage_root.as_ref().to_path_buf();
         let mut manager = Self {
             cartridges: HashMap::new(),
+            search_index: SearchIndex::default(),
             storage_root,
         };
         manager.load_or_initialize()?;
@@ -48,8 +193,24 @@ impl CartridgeManager {
         self.cartridges.values().cloned().collect()
     }
 
+    /// Cartridges whose name, description or tags contain any word of `query`,
+    /// ranked by number of matching words.
+    pub fn search(&self, query: &str) -> Vec<Cartridge> {
+        self.search_index
+            .search(query)
+            .into_iter()
+            .filter_map(|id| self.cartridges.get(&id).cloned())
+            .collect()
+    }
+
+    /// Look up a cartridge, falling back to the storage directory for ones
+    /// written by another manager. An invalid id or a missing or corrupt file yields `None`.
     pub fn get(&self, id: &str) -> Option<Cartridge> {
-        self.cartridges.get(id).cloned()
+        validate_id(id).ok()?;
+        self.cartridges
+            .get(id)
+            .cloned()
+            .or_else(|| read_cartridge(&self.cartridge_path(id)))
     }
 
     pub fn execute(&self, id: &str, input: Option<&str>) -> Result<Vec<u8>, CartridgeError> {
@@ -69,6 +230,7 @@ impl CartridgeManager {
     }
 
     pub fn create_cartridge(&mut self, cartridge: Cartridge) -> Result<(), CartridgeError> {
+        validate_id(&cartridge.id)?;
         if self.cartridges.contains_key(&cartridge.id) {
             return Err(CartridgeError::NotFound(format!(
                 "Cartridge already exists: {}",
@@ -77,8 +239,7 @@ impl CartridgeManager {
         }
 
         self.save_cartridge(&cartridge)?;
-        self.cartridges
-            .insert(cartridge.id.clone(), cartridge.clone());
+        self.remember(cartridge.clone());
         println!("📦 Created new cartridge: {}", cartridge.id);
         Ok(())
     }
@@ -89,19 +250,26 @@ impl CartridgeManager {
         }
 
         self.save_cartridge(&cartridge)?;
-        self.cartridges
-            .insert(cartridge.id.clone(), cartridge.clone());
+        self.remember(cartridge.clone());
         println!("📦 Updated cartridge: {}", cartridge.id);
         Ok(())
     }
 
     pub fn delete_cartridge(&mut self, id: &str) -> Result<(), CartridgeError> {
-        let path = Path::new(&self.storage_root).join(format!("{}.json", id));
-        if path.exists() {
-            fs::remove_file(path)?;
+        validate_id(id)?;
+        {
+            let _lock = IndexLock::acquire(&self.storage_root)?;
+            match fs::remove_file(self.cartridge_path(id)) {
+                Err(err) if err.kind() != ErrorKind::NotFound => return Err(err.into()),
+                _ => {}
+            }
+            self.update_index(|index| {
+                index.remove(id);
+            })?;
         }
 
         self.cartridges.remove(id);
+        self.search_index.remove(id);
         println!("🗑️ Deleted cartridge: {}", id);
         Ok(())
     }
@@ -117,18 +285,14 @@ impl CartridgeManager {
         for entry in fs::read_dir(&self.storage_root)? {
             let entry = entry?;
             let path = entry.path();
-            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            if !is_cartridge_file(&path) {
                 continue;
             }
 
-            let content = fs::read_to_string(&path)?;
-            if content.trim().is_empty() {
-                continue;
+            if let Some(cartridge) = read_cartridge(&path) {
+                self.remember(cartridge);
+                loaded_any = true;
             }
-
-            let cartridge: Cartridge = serde_json::from_str(&content)?;
-            self.cartridges.insert(cartridge.id.clone(), cartridge);
-            loaded_any = true;
         }
 
         if !loaded_any {
@@ -137,7 +301,7 @@ impl CartridgeManager {
             self.load_or_initialize()?;
         }
 
-        Ok(())
+        self.write_index()
     }
 
     fn write_default_cartridges(&mut self) -> Result<(), CartridgeError> {
@@ -147,6 +311,7 @@ impl CartridgeManager {
                 name: "Hello World".to_string(),
                 description: "Basic greeting cartridge demonstrating text output".to_string(),
                 code: "print(\"Hello, Sovereign AI!\")".to_string(),
+                code_format: CodeFormat::PixelAsm,
                 version: "1.0.0".to_string(),
                 author: Some("system".to_string()),
                 tags: vec!["demo".to_string(), "basic".to_string()],
@@ -156,6 +321,7 @@ impl CartridgeManager {
                 name: "Matrix Display".to_string(),
                 description: "Generates ASCII rain for the glyph expander".to_string(),
                 code: "generate_matrix_rain(128, 64)".to_string(),
+                code_format: CodeFormat::PixelAsm,
                 version: "1.0.0".to_string(),
                 author: Some("system".to_string()),
                 tags: vec!["display".to_string(), "demo".to_string()],
@@ -165,6 +331,7 @@ impl CartridgeManager {
                 name: "Glyph Expander".to_string(),
                 description: "Outputs ASCII ready for GPU glyph expansion".to_string(),
                 code: "expand_glyphs(\"SOVEREIGN AI\")".to_string(),
+                code_format: CodeFormat::PixelAsm,
                 version: "1.0.0".to_string(),
                 author: Some("system".to_string()),
                 tags: vec!["gpu".to_string(), "glyphs".to_string()],
@@ -173,16 +340,296 @@ impl CartridgeManager {
 
         for cartridge in defaults {
             self.save_cartridge(&cartridge)?;
-            self.cartridges.insert(cartridge.id.clone(), cartridge);
+            self.remember(cartridge);
         }
 
         Ok(())
     }
 
+    fn remember(&mut self, cartridge: Cartridge) {
+        self.search_index.insert(&cartridge);
+        self.cartridges.insert(cartridge.id.clone(), cartridge);
+    }
+
+    fn cartridge_path(&self, id: &str) -> PathBuf {
+        self.storage_root.join(format!("{}.json", id))
+    }
+
     fn save_cartridge(&self, cartridge: &Cartridge) -> Result<(), CartridgeError> {
-        let path = self.storage_root.join(format!("{}.json", cartridge.id));
         let content = serde_json::to_string_pretty(cartridge)?;
-        fs::write(path, content)?;
+        let _lock = IndexLock::acquire(&self.storage_root)?;
+        write_atomic(&self.cartridge_path(&cartridge.id), content.as_bytes())?;
+        self.update_index(|index| {
+            index.insert(
+                cartridge.id.clone(),
+                crate::models::hash_bytes(content.as_bytes()),
+            );
+        })
+    }
+
+    /// Apply `change` to the index on disk. The caller holds the `IndexLock`.
+    fn update_index(
+        &self,
+        change: impl FnOnce(&mut BTreeMap<String, String>),
+    ) -> Result<(), CartridgeError> {
+        let existing = fs::read_to_string(self.storage_root.join(INDEX_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok());
+        let mut index = match existing {
+            Some(index) => index,
+            None => self.scan_index()?,
+        };
+        change(&mut index);
+
+        let content = serde_json::to_string_pretty(&index)?;
+        write_atomic(&self.storage_root.join(INDEX_FILE), content.as_bytes())?;
         Ok(())
     }
+
+    /// Rebuild the index from the files on disk, so it also reflects other writers.
+    fn write_index(&self) -> Result<(), CartridgeError> {
+        let _lock = IndexLock::acquire(&self.storage_root)?;
+        let index = self.scan_index()?;
+        let content = serde_json::to_string_pretty(&index)?;
+        write_atomic(&self.storage_root.join(INDEX_FILE), content.as_bytes())?;
+        Ok(())
+    }
+
+    fn scan_index(&self) -> Result<BTreeMap<String, String>, CartridgeError> {
+        let mut index = BTreeMap::new();
+        for entry in fs::read_dir(&self.storage_root)? {
+            let path = entry?.path();
+            if !is_cartridge_file(&path) {
+                continue;
+            }
+            let Ok(content) = fs::read(&path) else {
+                continue; // removed since the directory was listed
+            };
+            if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                index.insert(id.to_string(), crate::models::hash_bytes(&content));
+            }
+        }
+        Ok(index)
+    }
+}
+
+/// Exclusive lock on a storage root's index, shared by every manager using that root,
+/// including ones in other processes. An OS advisory lock, so it blocks the calling thread
+/// while waiting and is released on drop or when a crashed holder's descriptor closes.
+struct IndexLock {
+    _file: fs::File,
+}
+
+impl IndexLock {
+    fn acquire(storage_root: &Path) -> std::io::Result<Self> {
+        let file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(storage_root.join(LOCK_FILE))?;
+        file.lock_exclusive()?;
+        Ok(Self { _file: file })
+    }
+}
+
+fn is_cartridge_file(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("json")
+        && path.file_name().and_then(|name| name.to_str()) != Some(INDEX_FILE)
+}
+
+/// Read one cartridge file. Missing and empty files are `None`; corrupt ones
+/// are logged and skipped so they can't take down the whole store.
+fn read_cartridge(path: &Path) -> Option<Cartridge> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!("Skipping unreadable cartridge {}: {}", path.display(), err);
+            return None;
+        }
+    };
+    if content.trim().is_empty() {
+        return None;
+    }
+
+    match serde_json::from_str(&content) {
+        Ok(cartridge) => Some(cartridge),
+        Err(err) => {
+            tracing::warn!("Skipping corrupt cartridge {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Write via a uniquely named temp file in the same directory and rename it
+/// into place, so readers never observe a partially written file.
+fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("cartridge");
+    let temp = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name,
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cartridge(id: &str) -> Cartridge {
+        Cartridge {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: "storage test".to_string(),
+            code: "HALT".to_string(),
+            code_format: CodeFormat::PixelAsm,
+            version: "1.0.0".to_string(),
+            author: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn index(root: &Path) -> BTreeMap<String, String> {
+        serde_json::from_str(&fs::read_to_string(root.join(INDEX_FILE)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn concurrent_writers_create_distinct_cartridges() {
+        let dir = tempfile::tempdir().unwrap();
+        CartridgeManager::new(dir.path()).unwrap();
+
+        let writers: Vec<_> = (0..8)
+            .map(|writer| {
+                let root = dir.path().to_path_buf();
+                std::thread::spawn(move || {
+                    let mut manager = CartridgeManager::new(&root).unwrap();
+                    for n in 0..4 {
+                        let id = format!("writer{}_{}", writer, n);
+                        manager.create_cartridge(cartridge(&id)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // Every writer's entry survives the others' index updates
+        let written = index(dir.path());
+        for writer in 0..8 {
+            for n in 0..4 {
+                let id = format!("writer{}_{}", writer, n);
+                let content = fs::read(dir.path().join(format!("{}.json", id))).unwrap();
+                assert_eq!(written[&id], crate::models::hash_bytes(&content));
+            }
+        }
+
+        let manager = CartridgeManager::new(dir.path()).unwrap();
+        let ids: Vec<String> = manager.list().into_iter().map(|c| c.id).collect();
+        for writer in 0..8 {
+            for n in 0..4 {
+                assert!(ids.contains(&format!("writer{}_{}", writer, n)));
+            }
+        }
+        assert_eq!(index(dir.path()).len(), ids.len());
+        let leftovers = fs::read_dir(dir.path())
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn corrupt_cartridge_is_skipped_on_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = CartridgeManager::new(dir.path()).unwrap();
+        manager.create_cartridge(cartridge("good")).unwrap();
+        fs::write(dir.path().join("broken.json"), "{ not json").unwrap();
+
+        let manager = CartridgeManager::new(dir.path()).unwrap();
+        let ids: Vec<String> = manager.list().into_iter().map(|c| c.id).collect();
+        assert!(ids.contains(&"good".to_string()));
+        assert!(!ids.contains(&"broken".to_string()));
+        assert!(manager.get("broken").is_none());
+        assert!(manager.get("missing").is_none());
+    }
+
+    #[test]
+    fn ids_outside_the_safe_charset_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = CartridgeManager::new(dir.path()).unwrap();
+        fs::write(dir.path().join("..json"), "{}").unwrap();
+
+        for id in ["../escape", "a/b", "", ".", "space id"] {
+            assert!(matches!(
+                manager.create_cartridge(cartridge(id)),
+                Err(CartridgeError::InvalidId(_))
+            ));
+            assert!(manager.get(id).is_none());
+            assert!(matches!(
+                manager.delete_cartridge(id),
+                Err(CartridgeError::InvalidId(_))
+            ));
+        }
+        assert!(dir.path().join("..json").exists());
+        manager.create_cartridge(cartridge("Valid_id-2")).unwrap();
+        assert!(manager.get("Valid_id-2").is_some());
+    }
+
+    #[test]
+    fn index_tracks_updates_and_deletes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = CartridgeManager::new(dir.path()).unwrap();
+        manager.create_cartridge(cartridge("tracked")).unwrap();
+        let before = index(dir.path())["tracked"].clone();
+
+        let mut updated = cartridge("tracked");
+        updated.code = "SET 0 1\nHALT".to_string();
+        manager.update_cartridge(updated).unwrap();
+        assert_ne!(index(dir.path())["tracked"], before);
+
+        manager.delete_cartridge("tracked").unwrap();
+        assert!(!index(dir.path()).contains_key("tracked"));
+        manager.delete_cartridge("tracked").unwrap();
+    }
+
+    #[test]
+    fn search_ranks_by_matches_and_follows_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = CartridgeManager::new(dir.path()).unwrap();
+        let mut plasma = cartridge("plasma");
+        plasma.description = "Animated plasma effect".to_string();
+        plasma.tags = vec!["effect".to_string()];
+        manager.create_cartridge(plasma.clone()).unwrap();
+        let mut fire = cartridge("fire");
+        fire.description = "Fire effect".to_string();
+        manager.create_cartridge(fire).unwrap();
+
+        let ids = |results: Vec<Cartridge>| -> Vec<String> {
+            results.into_iter().map(|c| c.id).collect()
+        };
+        assert_eq!(ids(manager.search("ANIMATED")), vec!["plasma"]);
+        assert_eq!(ids(manager.search("effect")), vec!["plasma", "fire"]);
+        assert!(manager.search("").is_empty());
+
+        plasma.description = "Static gradient".to_string();
+        manager.update_cartridge(plasma).unwrap();
+        assert!(manager.search("animated").is_empty());
+        assert_eq!(ids(manager.search("gradient")), vec!["plasma"]);
+
+        manager.delete_cartridge("plasma").unwrap();
+        assert!(manager.search("gradient").is_empty());
+        assert_eq!(ids(manager.search("effect")), vec!["fire"]);
+    }
 }
@@ -1,6 +1,7 @@
 use crate::errors::{AiRuntimeError, Result};
+use crate::monitor::TrackedMetric;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LmStudioConfig {
@@ -17,6 +18,130 @@ pub struct LoggingConfig {
     pub max_file_size_mb: u32,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MonitorConfig {
+    /// Optional metrics sampled into `SystemMetrics.extra`
+    #[serde(default)]
+    pub metrics: Vec<TrackedMetric>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DatabaseConfig {
+    /// How long a writer waits on a locked database before failing with `SQLITE_BUSY`
+    #[serde(default = "default_busy_timeout_ms")]
+    pub busy_timeout_ms: u64,
+    /// `PRAGMA synchronous` level (OFF, NORMAL, FULL or EXTRA); SQLite's default when unset
+    #[serde(default)]
+    pub synchronous: Option<String>,
+    /// `PRAGMA cache_size`; positive values are pages, negative values are KiB
+    #[serde(default)]
+    pub cache_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Deadline for `/api/gvpie/analyze` before it answers 504 with partial results
+    pub analysis_timeout_ms: u64,
+    /// Deadline for any request before it answers 408; longer than the analysis
+    /// deadline so that route can still return its partial results
+    pub request_timeout_ms: u64,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            analysis_timeout_ms: 30_000,
+            request_timeout_ms: 60_000,
+        }
+    }
+}
+
+/// Defaults and upper bounds for pixel programs submitted over the API
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PixelLimits {
+    pub default_canvas_width: u32,
+    pub default_canvas_height: u32,
+    pub default_max_cycles: u64,
+    pub max_canvas_width: u32,
+    pub max_canvas_height: u32,
+    pub max_cycles_limit: u64,
+    /// Lower cycle bound for backend diffs, which replay the program several times per backend
+    pub max_diff_cycles: u64,
+}
+
+impl PixelLimits {
+    /// Fill in unset values from the defaults, rejecting any above their maximum
+    pub fn resolve(
+        &self,
+        canvas_width: Option<u32>,
+        canvas_height: Option<u32>,
+        max_cycles: Option<u64>,
+    ) -> std::result::Result<(u32, u32, u64), String> {
+        fn bounded<T: PartialOrd + std::fmt::Display>(
+            name: &str,
+            value: T,
+            max: T,
+        ) -> std::result::Result<T, String> {
+            if value > max {
+                return Err(format!("{} {} exceeds the maximum of {}", name, value, max));
+            }
+            Ok(value)
+        }
+
+        Ok((
+            bounded(
+                "canvas_width",
+                canvas_width.unwrap_or(self.default_canvas_width),
+                self.max_canvas_width,
+            )?,
+            bounded(
+                "canvas_height",
+                canvas_height.unwrap_or(self.default_canvas_height),
+                self.max_canvas_height,
+            )?,
+            bounded(
+                "max_cycles",
+                max_cycles.unwrap_or(self.default_max_cycles),
+                self.max_cycles_limit,
+            )?,
+        ))
+    }
+
+    /// Like `resolve`, with cycles further bounded by `max_diff_cycles`
+    pub fn resolve_diff(
+        &self,
+        canvas_width: Option<u32>,
+        canvas_height: Option<u32>,
+        max_cycles: Option<u64>,
+    ) -> std::result::Result<(u32, u32, u64), String> {
+        let (canvas_width, canvas_height, max_cycles) =
+            self.resolve(canvas_width, canvas_height, max_cycles)?;
+        if max_cycles > self.max_diff_cycles {
+            return Err(format!(
+                "max_cycles {} exceeds the diff maximum of {}",
+                max_cycles, self.max_diff_cycles
+            ));
+        }
+        Ok((canvas_width, canvas_height, max_cycles))
+    }
+}
+
+impl Default for PixelLimits {
+    fn default() -> Self {
+        Self {
+            default_canvas_width: 64,
+            default_canvas_height: 64,
+            default_max_cycles: 1024,
+            max_canvas_width: 1024,
+            max_canvas_height: 1024,
+            max_cycles_limit: 1_000_000,
+            max_diff_cycles: 10_000,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     #[serde(default = "default_database_url")]
@@ -28,6 +153,14 @@ pub struct Config {
     pub lm_studio: LmStudioConfig,
     #[serde(default)]
     pub logging: LoggingConfig,
+    #[serde(default)]
+    pub monitor: MonitorConfig,
+    #[serde(default)]
+    pub database: DatabaseConfig,
+    #[serde(default)]
+    pub pixel: PixelLimits,
+    #[serde(default)]
+    pub api: ApiConfig,
 }
 
 fn default_database_url() -> String {
@@ -38,6 +171,10 @@ fn default_http_port() -> u16 {
     8080
 }
 
+fn default_busy_timeout_ms() -> u64 {
+    5000
+}
+
 impl Default for LmStudioConfig {
     fn default() -> Self {
         Self {
@@ -59,6 +196,16 @@ impl Default for LoggingConfig {
     }
 }
 
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            busy_timeout_ms: default_busy_timeout_ms(),
+            synchronous: None,
+            cache_size: None,
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -67,11 +214,29 @@ impl Default for Config {
             gpu_device_id: None,
             lm_studio: LmStudioConfig::default(),
             logging: LoggingConfig::default(),
+            monitor: MonitorConfig::default(),
+            database: DatabaseConfig::default(),
+            pixel: PixelLimits::default(),
+            api: ApiConfig::default(),
         }
     }
 }
 
 impl Config {
+    /// SQLite path for the experience database: `GVPIE_DB_PATH` if set, else `database_url`
+    /// without its `sqlite:` scheme
+    pub fn database_path(&self) -> PathBuf {
+        if let Ok(path) = std::env::var("GVPIE_DB_PATH") {
+            return PathBuf::from(path);
+        }
+        let url = self.database_url.as_str();
+        let path = url
+            .strip_prefix("sqlite://")
+            .or_else(|| url.strip_prefix("sqlite:"))
+            .unwrap_or(url);
+        PathBuf::from(path)
+    }
+
     pub fn load() -> Result<Self> {
         let mut config = Config::default();
 
@@ -97,6 +262,10 @@ impl Config {
                     config.gpu_device_id = merged.gpu_device_id;
                     config.lm_studio = merged.lm_studio;
                     config.logging = merged.logging;
+                    config.monitor = merged.monitor;
+                    config.database = merged.database;
+                    config.pixel = merged.pixel;
+                    config.api = merged.api;
                 }
             }
         }
@@ -104,3 +273,53 @@ impl Config {
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_limits_fill_defaults_and_reject_oversize() {
+        let limits = PixelLimits::default();
+        assert_eq!(limits.resolve(None, None, None), Ok((64, 64, 1024)));
+        assert_eq!(
+            limits.resolve(Some(1024), Some(8), Some(10)),
+            Ok((1024, 8, 10))
+        );
+
+        let err = limits.resolve(Some(8192), None, None).unwrap_err();
+        assert_eq!(err, "canvas_width 8192 exceeds the maximum of 1024");
+        assert!(limits
+            .resolve(None, None, Some(limits.max_cycles_limit + 1))
+            .unwrap_err()
+            .starts_with("max_cycles"));
+    }
+
+    #[test]
+    fn database_path_strips_the_sqlite_scheme() {
+        let config = |url: &str| Config {
+            database_url: url.to_string(),
+            ..Config::default()
+        };
+        assert_eq!(Config::default().database_path(), PathBuf::from("gvpie.db"));
+        assert_eq!(
+            config("sqlite:///var/lib/gvpie.db").database_path(),
+            PathBuf::from("/var/lib/gvpie.db")
+        );
+        assert_eq!(
+            config(":memory:").database_path(),
+            PathBuf::from(":memory:")
+        );
+    }
+
+    #[test]
+    fn diff_limits_cap_cycles_below_the_run_limit() {
+        let limits = PixelLimits::default();
+        assert!(limits.max_diff_cycles < limits.max_cycles_limit);
+        assert_eq!(limits.resolve_diff(None, None, None), Ok((64, 64, 1024)));
+        assert_eq!(
+            limits.resolve_diff(None, None, Some(limits.max_cycles_limit)),
+            Err("max_cycles 1000000 exceeds the diff maximum of 10000".to_string())
+        );
+    }
+}
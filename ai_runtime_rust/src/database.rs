@@ -1,14 +1,17 @@
 // gvpie/ai-runtime/src/database.rs
 //! Experience Database module for AI Runtime
 //!
-//! Provides an asynchronous SQLite persistence layer for system metrics, decisions, and events.
+//! Provides an asynchronous SQLite persistence layer for system metrics, decisions, events,
+//! and cartridge execution history.
 //! Based on Python's ai_runtime/core/memory.py
 
+use crate::config::DatabaseConfig;
 use crate::errors::{AiRuntimeError, Result};
 use chrono::{DateTime, Duration, Utc};
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use tokio::sync::Mutex;
 
@@ -40,6 +43,15 @@ pub struct EventRecord {
     pub created_at: DateTime<Utc>,
 }
 
+/// One recorded cartridge execution, replayable with the same input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartridgeExecutionRecord {
+    pub cartridge_id: String,
+    pub input: Option<String>,
+    pub executed_at: DateTime<Utc>,
+    pub result_hash: String,
+}
+
 /// Pattern analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatternAnalysis {
@@ -53,6 +65,18 @@ pub struct ResourceTrends {
     pub disk_avg: f32,
 }
 
+/// Aggregated activity over a trailing window, served by `/api/stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeStats {
+    pub window_hours: i64,
+    pub since: DateTime<Utc>,
+    pub resources: ResourceTrends,
+    pub decisions_by_action: BTreeMap<String, u64>,
+    pub events_by_kind: BTreeMap<String, u64>,
+    pub cartridge_executions: u64,
+    pub executions_by_cartridge: BTreeMap<String, u64>,
+}
+
 /// Trend analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrendAnalysis {
@@ -63,7 +87,51 @@ pub struct TrendAnalysis {
     pub samples: usize,
 }
 
+/// Ordered schema migrations; a migration's version is its index plus one.
+/// Append new migrations, never edit applied ones.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE IF NOT EXISTS metrics (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         recorded_at TEXT NOT NULL,
+         cpu REAL,
+         memory REAL,
+         disk REAL,
+         state_json TEXT NOT NULL
+     );
+
+     CREATE TABLE IF NOT EXISTS decisions (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         decided_at TEXT NOT NULL,
+         action TEXT,
+         confidence REAL,
+         decision_json TEXT NOT NULL,
+         state_json TEXT NOT NULL
+     );
+
+     CREATE TABLE IF NOT EXISTS events (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         kind TEXT NOT NULL,
+         payload_json TEXT NOT NULL,
+         created_at TEXT NOT NULL
+     );
+
+     CREATE TABLE IF NOT EXISTS cartridge_executions (
+         id INTEGER PRIMARY KEY AUTOINCREMENT,
+         cartridge_id TEXT NOT NULL,
+         input TEXT,
+         executed_at TEXT NOT NULL,
+         result_hash TEXT NOT NULL
+     );
+     CREATE INDEX IF NOT EXISTS idx_cartridge_executions_cartridge
+         ON cartridge_executions (cartridge_id, id);",
+];
+
+/// Schema version after all migrations have been applied
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
 /// Asynchronous interface around a SQLite datastore
+#[derive(Debug)]
 pub struct ExperienceDB {
     connection: Mutex<Connection>,
     db_path: PathBuf,
@@ -72,6 +140,11 @@ pub struct ExperienceDB {
 impl ExperienceDB {
     /// Create a new database connection and initialize schema
     pub async fn new(db_path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_config(db_path, &DatabaseConfig::default()).await
+    }
+
+    /// Like `new`, applying the connection pragmas from `config`
+    pub async fn with_config(db_path: impl AsRef<Path>, config: &DatabaseConfig) -> Result<Self> {
         let db_path = db_path.as_ref().to_path_buf();
 
         // Create parent directory if it doesn't exist
@@ -79,38 +152,15 @@ impl ExperienceDB {
             tokio::fs::create_dir_all(parent).await?;
         }
 
-        let conn = Connection::open(&db_path)?;
+        let mut conn = Connection::open(&db_path)?;
+        Self::apply_pragmas(&conn, config)?;
 
         // Initialize database with WAL mode and foreign keys
         conn.execute_batch(
             "PRAGMA journal_mode = WAL;
-             PRAGMA foreign_keys = ON;
-
-             CREATE TABLE IF NOT EXISTS metrics (
-                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                 recorded_at TEXT NOT NULL,
-                 cpu REAL,
-                 memory REAL,
-                 disk REAL,
-                 state_json TEXT NOT NULL
-             );
-
-             CREATE TABLE IF NOT EXISTS decisions (
-                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                 decided_at TEXT NOT NULL,
-                 action TEXT,
-                 confidence REAL,
-                 decision_json TEXT NOT NULL,
-                 state_json TEXT NOT NULL
-             );
-
-             CREATE TABLE IF NOT EXISTS events (
-                 id INTEGER PRIMARY KEY AUTOINCREMENT,
-                 kind TEXT NOT NULL,
-                 payload_json TEXT NOT NULL,
-                 created_at TEXT NOT NULL
-             );",
+             PRAGMA foreign_keys = ON;",
         )?;
+        Self::migrate(&mut conn)?;
 
         Ok(Self {
             connection: Mutex::new(conn),
@@ -177,6 +227,50 @@ impl ExperienceDB {
         Ok(result)
     }
 
+    /// Logged decisions, newest first, optionally filtered by action and a lower time bound
+    pub async fn query_decisions(
+        &self,
+        action: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        limit: usize,
+    ) -> Result<Vec<DecisionRecord>> {
+        let conn = self.connection.lock().await;
+        let mut stmt = conn.prepare(
+            "SELECT decided_at, action, confidence, decision_json, state_json FROM decisions
+             WHERE (?1 IS NULL OR action = ?1)
+               AND (?2 IS NULL OR julianday(decided_at) >= julianday(?2))
+             ORDER BY id DESC LIMIT ?3",
+        )?;
+
+        let rows = stmt.query_map(
+            params![action, since.map(|dt| dt.to_rfc3339()), limit],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<f32>>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            },
+        )?;
+
+        let mut decisions = Vec::new();
+        for row in rows {
+            let (decided_at, action, confidence, decision_json, state_json) = row?;
+            decisions.push(DecisionRecord {
+                decided_at: DateTime::parse_from_rfc3339(&decided_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+                action,
+                confidence,
+                decision_json: serde_json::from_str(&decision_json)?,
+                state_json: serde_json::from_str(&state_json)?,
+            });
+        }
+        Ok(decisions)
+    }
+
     /// Analyze patterns in system metrics
     pub async fn analyze_patterns(&self, window: usize) -> Result<PatternAnalysis> {
         let conn = self.connection.lock().await;
@@ -189,14 +283,35 @@ impl ExperienceDB {
             })?
             .collect::<std::result::Result<Vec<_>, _>>()?;
 
+        Ok(Self::average_resources(&rows))
+    }
+
+    /// Like `analyze_patterns`, averaging every metric recorded at or after `since`
+    pub async fn analyze_patterns_since(&self, since: DateTime<Utc>) -> Result<PatternAnalysis> {
+        let conn = self.connection.lock().await;
+        let mut stmt = conn.prepare(
+            "SELECT cpu, memory, disk FROM metrics
+             WHERE julianday(recorded_at) >= julianday(?1)",
+        )?;
+
+        let rows: Vec<(Option<f32>, Option<f32>, Option<f32>)> = stmt
+            .query_map(params![since.to_rfc3339()], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(Self::average_resources(&rows))
+    }
+
+    fn average_resources(rows: &[(Option<f32>, Option<f32>, Option<f32>)]) -> PatternAnalysis {
         if rows.is_empty() {
-            return Ok(PatternAnalysis {
+            return PatternAnalysis {
                 resource_trends: ResourceTrends {
                     cpu_avg: 0.0,
                     memory_avg: 0.0,
                     disk_avg: 0.0,
                 },
-            });
+            };
         }
 
         let cpu_sum: f32 = rows.iter().map(|(cpu, _, _)| cpu.unwrap_or(0.0)).sum();
@@ -204,13 +319,67 @@ impl ExperienceDB {
         let disk_sum: f32 = rows.iter().map(|(_, _, disk)| disk.unwrap_or(0.0)).sum();
         let count = rows.len() as f32;
 
-        Ok(PatternAnalysis {
+        PatternAnalysis {
             resource_trends: ResourceTrends {
                 cpu_avg: cpu_sum / count,
                 memory_avg: mem_sum / count,
                 disk_avg: disk_sum / count,
             },
-        })
+        }
+    }
+
+    /// Decisions logged at or after `since`, counted per action ("none" for unlabelled ones)
+    pub async fn decision_counts_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<BTreeMap<String, u64>> {
+        let conn = self.connection.lock().await;
+        Self::count_grouped(
+            &conn,
+            "SELECT COALESCE(action, 'none'), COUNT(*) FROM decisions
+             WHERE julianday(decided_at) >= julianday(?1) GROUP BY 1",
+            since,
+        )
+    }
+
+    /// Events recorded at or after `since`, counted per kind
+    pub async fn event_counts_since(&self, since: DateTime<Utc>) -> Result<BTreeMap<String, u64>> {
+        let conn = self.connection.lock().await;
+        Self::count_grouped(
+            &conn,
+            "SELECT kind, COUNT(*) FROM events
+             WHERE julianday(created_at) >= julianday(?1) GROUP BY kind",
+            since,
+        )
+    }
+
+    /// Cartridge executions at or after `since`, counted per cartridge.
+    /// Only executions still inside the per-cartridge history bound are counted.
+    pub async fn cartridge_execution_counts_since(
+        &self,
+        since: DateTime<Utc>,
+    ) -> Result<BTreeMap<String, u64>> {
+        let conn = self.connection.lock().await;
+        Self::count_grouped(
+            &conn,
+            "SELECT cartridge_id, COUNT(*) FROM cartridge_executions
+             WHERE julianday(executed_at) >= julianday(?1) GROUP BY cartridge_id",
+            since,
+        )
+    }
+
+    fn count_grouped(
+        conn: &Connection,
+        sql: &str,
+        since: DateTime<Utc>,
+    ) -> Result<BTreeMap<String, u64>> {
+        let mut stmt = conn.prepare(sql)?;
+        let counts = stmt
+            .query_map(params![since.to_rfc3339()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, u64>(1)?))
+            })?
+            .collect::<std::result::Result<BTreeMap<_, _>, _>>()?;
+        Ok(counts)
     }
 
     /// Analyze trends for a specific metric over time
@@ -288,6 +457,69 @@ impl ExperienceDB {
         Ok(())
     }
 
+    /// Record a cartridge execution, keeping only the newest `keep` entries per cartridge
+    pub async fn record_cartridge_execution(
+        &self,
+        record: &CartridgeExecutionRecord,
+        keep: usize,
+    ) -> Result<()> {
+        let conn = self.connection.lock().await;
+        conn.execute(
+            "INSERT INTO cartridge_executions (cartridge_id, input, executed_at, result_hash)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                record.cartridge_id,
+                record.input,
+                record.executed_at.to_rfc3339(),
+                record.result_hash
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM cartridge_executions
+             WHERE cartridge_id = ?1 AND id NOT IN (
+                 SELECT id FROM cartridge_executions
+                 WHERE cartridge_id = ?1 ORDER BY id DESC LIMIT ?2
+             )",
+            params![record.cartridge_id, keep],
+        )?;
+        Ok(())
+    }
+
+    /// Execution history for a cartridge, oldest first
+    pub async fn cartridge_history(
+        &self,
+        cartridge_id: &str,
+    ) -> Result<Vec<CartridgeExecutionRecord>> {
+        let conn = self.connection.lock().await;
+        let mut stmt = conn.prepare(
+            "SELECT cartridge_id, input, executed_at, result_hash FROM cartridge_executions
+             WHERE cartridge_id = ?1 ORDER BY id ASC",
+        )?;
+
+        let rows = stmt.query_map(params![cartridge_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?;
+
+        let mut history = Vec::new();
+        for row in rows {
+            let (cartridge_id, input, executed_at, result_hash) = row?;
+            history.push(CartridgeExecutionRecord {
+                cartridge_id,
+                input,
+                executed_at: DateTime::parse_from_rfc3339(&executed_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+                result_hash,
+            });
+        }
+        Ok(history)
+    }
+
     /// Extract a metric value from nested JSON using dot notation
     fn extract_metric(state: &JsonValue, key: &str) -> Option<f32> {
         let parts: Vec<&str> = key.split('.').collect();
@@ -303,6 +535,74 @@ impl ExperienceDB {
         }
     }
 
+    /// Run a trivial query to confirm the connection is usable
+    pub async fn ping(&self) -> Result<()> {
+        let conn = self.connection.lock().await;
+        conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))?;
+        Ok(())
+    }
+
+    /// Apply every migration newer than the recorded schema version, each in its own transaction
+    fn migrate(conn: &mut Connection) -> Result<()> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (
+                 version INTEGER PRIMARY KEY,
+                 applied_at TEXT NOT NULL
+             );",
+        )?;
+        let current = Self::read_schema_version(conn)?;
+        if current > SCHEMA_VERSION {
+            return Err(AiRuntimeError::config(format!(
+                "database schema version {} is newer than this build supports ({})",
+                current, SCHEMA_VERSION
+            )));
+        }
+
+        for (index, sql) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+            let tx = conn.transaction()?;
+            tx.execute_batch(sql)?;
+            tx.execute(
+                "INSERT INTO schema_version (version, applied_at) VALUES (?1, ?2)",
+                params![index as u32 + 1, Utc::now().to_rfc3339()],
+            )?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    fn read_schema_version(conn: &Connection) -> Result<u32> {
+        Ok(conn.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM schema_version",
+            [],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Schema version recorded in the database
+    pub async fn schema_version(&self) -> Result<u32> {
+        let conn = self.connection.lock().await;
+        Self::read_schema_version(&conn)
+    }
+
+    fn apply_pragmas(conn: &Connection, config: &DatabaseConfig) -> Result<()> {
+        conn.busy_timeout(std::time::Duration::from_millis(config.busy_timeout_ms))?;
+
+        if let Some(level) = &config.synchronous {
+            let level = level.to_ascii_uppercase();
+            if !matches!(level.as_str(), "OFF" | "NORMAL" | "FULL" | "EXTRA") {
+                return Err(AiRuntimeError::config(format!(
+                    "invalid database synchronous level: {}",
+                    level
+                )));
+            }
+            conn.pragma_update(None, "synchronous", &level)?;
+        }
+        if let Some(pages) = config.cache_size {
+            conn.pragma_update(None, "cache_size", pages)?;
+        }
+        Ok(())
+    }
+
     /// Get database path
     pub fn path(&self) -> &Path {
         &self.db_path
@@ -358,4 +658,234 @@ mod tests {
         let patterns = db.analyze_patterns(10).await.unwrap();
         assert!(patterns.resource_trends.cpu_avg > 0.0);
     }
+
+    #[tokio::test]
+    async fn test_cartridge_history_is_bounded() {
+        let dir = tempdir().unwrap();
+        let db = ExperienceDB::new(dir.path().join("test.db")).await.unwrap();
+
+        for i in 0..5 {
+            let record = CartridgeExecutionRecord {
+                cartridge_id: "demo".to_string(),
+                input: Some(format!("run {}", i)),
+                executed_at: Utc::now(),
+                result_hash: "abc".to_string(),
+            };
+            db.record_cartridge_execution(&record, 3).await.unwrap();
+        }
+
+        let history = db.cartridge_history("demo").await.unwrap();
+        let inputs: Vec<_> = history.iter().map(|r| r.input.as_deref()).collect();
+        assert_eq!(inputs, vec![Some("run 2"), Some("run 3"), Some("run 4")]);
+        assert!(db.cartridge_history("other").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_migrations_apply_once() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+
+        let db = ExperienceDB::new(&db_path).await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+        db.log_metrics(&sample_metrics()).await.unwrap();
+        drop(db);
+
+        let db = ExperienceDB::new(&db_path).await.unwrap();
+        assert_eq!(db.schema_version().await.unwrap(), SCHEMA_VERSION);
+        let conn = db.connection.lock().await;
+        let applied: u32 = conn
+            .query_row("SELECT COUNT(*) FROM schema_version", [], |row| row.get(0))
+            .unwrap();
+        let metrics: u32 = conn
+            .query_row("SELECT COUNT(*) FROM metrics", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(applied, SCHEMA_VERSION);
+        assert_eq!(metrics, 1);
+    }
+
+    #[tokio::test]
+    async fn test_newer_schema_is_rejected() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        drop(ExperienceDB::new(&db_path).await.unwrap());
+
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, applied_at) VALUES (?1, '')",
+            params![SCHEMA_VERSION + 1],
+        )
+        .unwrap();
+        drop(conn);
+
+        assert!(ExperienceDB::new(&db_path).await.is_err());
+    }
+
+    async fn seed_decisions(db: &ExperienceDB) -> DateTime<Utc> {
+        let now = Utc::now();
+        let seeded = [
+            ("scale_up", 48),
+            ("restart", 30),
+            ("scale_up", 12),
+            ("restart", 2),
+            ("scale_up", 1),
+        ];
+        for (action, hours_ago) in seeded {
+            let decision = DecisionRecord {
+                decided_at: now - Duration::hours(hours_ago),
+                action: Some(action.to_string()),
+                confidence: Some(0.5),
+                decision_json: serde_json::json!({ "hours_ago": hours_ago }),
+                state_json: serde_json::json!({}),
+            };
+            db.log_decision(&decision).await.unwrap();
+        }
+        now
+    }
+
+    fn hours_ago(records: &[DecisionRecord]) -> Vec<i64> {
+        records
+            .iter()
+            .map(|r| r.decision_json["hours_ago"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_query_decisions_by_action() {
+        let dir = tempdir().unwrap();
+        let db = ExperienceDB::new(dir.path().join("test.db")).await.unwrap();
+        seed_decisions(&db).await;
+
+        let all = db.query_decisions(None, None, 10).await.unwrap();
+        assert_eq!(hours_ago(&all), vec![1, 2, 12, 30, 48]);
+
+        let restarts = db.query_decisions(Some("restart"), None, 10).await.unwrap();
+        assert_eq!(hours_ago(&restarts), vec![2, 30]);
+        assert!(restarts
+            .iter()
+            .all(|r| r.action.as_deref() == Some("restart")));
+
+        let limited = db.query_decisions(Some("scale_up"), None, 2).await.unwrap();
+        assert_eq!(hours_ago(&limited), vec![1, 12]);
+        assert!(db
+            .query_decisions(Some("shutdown"), None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_decisions_by_time() {
+        let dir = tempdir().unwrap();
+        let db = ExperienceDB::new(dir.path().join("test.db")).await.unwrap();
+        let now = seed_decisions(&db).await;
+
+        let last_day = db
+            .query_decisions(None, Some(now - Duration::hours(24)), 10)
+            .await
+            .unwrap();
+        assert_eq!(hours_ago(&last_day), vec![1, 2, 12]);
+
+        let future = db
+            .query_decisions(None, Some(now + Duration::hours(1)), 10)
+            .await
+            .unwrap();
+        assert!(future.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_query_decisions_combined_filters() {
+        let dir = tempdir().unwrap();
+        let db = ExperienceDB::new(dir.path().join("test.db")).await.unwrap();
+        let now = seed_decisions(&db).await;
+
+        let recent_scale_ups = db
+            .query_decisions(Some("scale_up"), Some(now - Duration::hours(24)), 10)
+            .await
+            .unwrap();
+        assert_eq!(hours_ago(&recent_scale_ups), vec![1, 12]);
+        assert_eq!(recent_scale_ups[0].confidence, Some(0.5));
+
+        let recent_restarts = db
+            .query_decisions(Some("restart"), Some(now - Duration::hours(6)), 10)
+            .await
+            .unwrap();
+        assert_eq!(hours_ago(&recent_restarts), vec![2]);
+    }
+
+    fn sample_metrics() -> SystemMetricsRecord {
+        SystemMetricsRecord {
+            recorded_at: Utc::now(),
+            cpu: Some(10.0),
+            memory: Some(20.0),
+            disk: Some(30.0),
+            state_json: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writer_waits_for_lock() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let config = DatabaseConfig {
+            busy_timeout_ms: 2000,
+            synchronous: Some("normal".to_string()),
+            cache_size: Some(-2048),
+        };
+        let db = ExperienceDB::with_config(&db_path, &config).await.unwrap();
+
+        // A second connection holds the write lock briefly, as another process would
+        let other = Connection::open(&db_path).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE;").unwrap();
+        let holder = std::thread::spawn(move || {
+            other
+                .execute(
+                    "INSERT INTO events (kind, payload_json, created_at) VALUES ('x', '{}', '')",
+                    [],
+                )
+                .unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(150));
+            other.execute_batch("COMMIT;").unwrap();
+        });
+
+        db.log_metrics(&sample_metrics()).await.unwrap();
+        holder.join().unwrap();
+        assert_eq!(
+            db.analyze_patterns(10)
+                .await
+                .unwrap()
+                .resource_trends
+                .cpu_avg,
+            10.0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_zero_busy_timeout_fails_fast() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test.db");
+        let config = DatabaseConfig {
+            busy_timeout_ms: 0,
+            ..DatabaseConfig::default()
+        };
+        let db = ExperienceDB::with_config(&db_path, &config).await.unwrap();
+
+        let other = Connection::open(&db_path).unwrap();
+        other.execute_batch("BEGIN IMMEDIATE;").unwrap();
+        assert!(db.log_metrics(&sample_metrics()).await.is_err());
+        other.execute_batch("COMMIT;").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_synchronous_level_is_rejected() {
+        let dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            synchronous: Some("sometimes".to_string()),
+            ..DatabaseConfig::default()
+        };
+        assert!(
+            ExperienceDB::with_config(dir.path().join("test.db"), &config)
+                .await
+                .is_err()
+        );
+    }
 }
@@ -29,6 +29,10 @@ pub enum AiRuntimeError {
     ValidationError(String),
     #[error("LLM error: {0}")]
     LlmError(String),
+    #[error("GPU unavailable: {0}")]
+    GpuUnavailable(String),
+    #[error("GPU busy: {0}")]
+    GpuBusy(String),
     #[error("Unknown error")]
     Unknown,
 }
@@ -57,4 +61,12 @@ impl AiRuntimeError {
     pub fn llm(msg: impl Into<String>) -> Self {
         Self::LlmError(msg.into())
     }
+
+    pub fn gpu_unavailable(msg: impl Into<String>) -> Self {
+        Self::GpuUnavailable(msg.into())
+    }
+
+    pub fn gpu_busy(msg: impl Into<String>) -> Self {
+        Self::GpuBusy(msg.into())
+    }
 }
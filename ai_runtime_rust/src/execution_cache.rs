@@ -0,0 +1,150 @@
+//! Bounded LRU cache of cartridge execution results
+//!
+//! Entries are keyed by cartridge id, input hash and cartridge version.
+//! Each cartridge also has a generation counter that `invalidate` bumps, so an
+//! execution that started before an update can't repopulate the cache with
+//! stale output once it finishes.
+
+use std::collections::{HashMap, VecDeque};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExecutionKey {
+    pub cartridge_id: String,
+    pub input_hash: String,
+    pub version: String,
+}
+
+impl ExecutionKey {
+    pub fn new(cartridge: &crate::Cartridge, input: Option<&str>) -> Self {
+        // Distinguish "no input" from an empty input string
+        let input_hash = match input {
+            Some(input) => crate::models::hash_bytes(input.as_bytes()),
+            None => String::new(),
+        };
+        Self {
+            cartridge_id: cartridge.id.clone(),
+            input_hash,
+            version: cartridge.version.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExecutionCache<V> {
+    capacity: usize,
+    entries: HashMap<ExecutionKey, V>,
+    /// Least recently used at the front
+    order: VecDeque<ExecutionKey>,
+    generations: HashMap<String, u64>,
+}
+
+impl<V: Clone> ExecutionCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            generations: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Current generation of a cartridge; pass it back to `insert`
+    pub fn generation(&self, cartridge_id: &str) -> u64 {
+        self.generations.get(cartridge_id).copied().unwrap_or(0)
+    }
+
+    pub fn get(&mut self, key: &ExecutionKey) -> Option<V> {
+        let value = self.entries.get(key)?.clone();
+        self.touch(key);
+        Some(value)
+    }
+
+    /// Cache `value` unless the cartridge was invalidated since `generation` was read
+    pub fn insert(&mut self, key: ExecutionKey, value: V, generation: u64) {
+        if self.capacity == 0 || generation != self.generation(&key.cartridge_id) {
+            return;
+        }
+        if self.entries.insert(key.clone(), value).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    /// Drop every entry for a cartridge and fence off in-flight executions
+    pub fn invalidate(&mut self, cartridge_id: &str) {
+        *self
+            .generations
+            .entry(cartridge_id.to_string())
+            .or_insert(0) += 1;
+        self.entries
+            .retain(|key, _| key.cartridge_id != cartridge_id);
+        self.order.retain(|key| key.cartridge_id != cartridge_id);
+    }
+
+    fn touch(&mut self, key: &ExecutionKey) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            if let Some(key) = self.order.remove(pos) {
+                self.order.push_back(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str, input: &str) -> ExecutionKey {
+        ExecutionKey {
+            cartridge_id: id.to_string(),
+            input_hash: input.to_string(),
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache = ExecutionCache::new(2);
+        cache.insert(key("a", "1"), 1, 0);
+        cache.insert(key("b", "1"), 2, 0);
+        assert_eq!(cache.get(&key("a", "1")), Some(1));
+
+        cache.insert(key("c", "1"), 3, 0);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&key("b", "1")), None);
+        assert_eq!(cache.get(&key("a", "1")), Some(1));
+        assert_eq!(cache.get(&key("c", "1")), Some(3));
+    }
+
+    #[test]
+    fn invalidation_rejects_stale_inserts() {
+        let mut cache = ExecutionCache::new(8);
+        let generation = cache.generation("a");
+        cache.insert(key("a", "1"), 1, generation);
+        cache.insert(key("b", "1"), 2, cache.generation("b"));
+
+        cache.invalidate("a");
+        assert_eq!(cache.get(&key("a", "1")), None);
+        assert_eq!(cache.get(&key("b", "1")), Some(2));
+
+        // An execution that began before the invalidation must not be cached
+        cache.insert(key("a", "2"), 9, generation);
+        assert_eq!(cache.get(&key("a", "2")), None);
+        cache.insert(key("a", "2"), 10, cache.generation("a"));
+        assert_eq!(cache.get(&key("a", "2")), Some(10));
+    }
+}
@@ -1,17 +1,156 @@
 use crate::pixel_vm::PixelProgramResponse;
+use crate::AiRuntimeError;
 use gvpie_core::{
     gpu::OptimizedGpuExecutionScheduler,
     pixel_language::{ExecutionErrorCode, PixelInstruction},
     GpuCore,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+
+/// GPU dispatches allowed to run at once unless `GVPIE_MAX_GPU_CONCURRENCY` says otherwise
+pub const DEFAULT_MAX_GPU_CONCURRENCY: usize = 1;
+
+/// GPU dispatches allowed to wait for a permit before new ones are turned away
+pub const GPU_DISPATCH_QUEUE_DEPTH: usize = 32;
+
+/// Program data handed to the GPU, plus the device budget where known.
+///
+/// Byte counts are the host-side size of instruction slices while they are dispatched, by the
+/// bridge or by pixel VM executions sharing its `ProgramTracker`; buffers created inside
+/// `gvpie-core` are not visible here, so this is not VRAM usage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GpuMemoryReport {
+    pub tracked_programs: usize,
+    pub program_bytes: u64,
+    pub peak_program_bytes: u64,
+    /// Total VRAM as reported by the driver; `None` where it isn't exposed
+    pub budget_bytes: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct BufferLedger {
+    next_id: u64,
+    live: HashMap<u64, u64>,
+    program_bytes: u64,
+    peak_program_bytes: u64,
+}
+
+/// Program bytes counted in a `ProgramTracker`'s totals until dropped
+#[derive(Debug)]
+pub struct TrackedProgram {
+    id: u64,
+    bytes: u64,
+    ledger: Arc<std::sync::Mutex<BufferLedger>>,
+}
+
+impl TrackedProgram {
+    pub fn size(&self) -> u64 {
+        self.bytes
+    }
+}
+
+impl Drop for TrackedProgram {
+    fn drop(&mut self) {
+        let mut ledger = self.ledger.lock().unwrap();
+        if let Some(bytes) = ledger.live.remove(&self.id) {
+            ledger.program_bytes -= bytes;
+        }
+    }
+}
+
+/// Ledger of program bytes in flight on the GPU; clones share the same totals
+#[derive(Debug, Clone, Default)]
+pub struct ProgramTracker {
+    ledger: Arc<std::sync::Mutex<BufferLedger>>,
+}
+
+impl ProgramTracker {
+    /// Count `bytes` of program data until the handle is dropped
+    pub fn track(&self, bytes: u64) -> TrackedProgram {
+        let mut ledger = self.ledger.lock().unwrap();
+        let id = ledger.next_id;
+        ledger.next_id += 1;
+        ledger.live.insert(id, bytes);
+        ledger.program_bytes += bytes;
+        ledger.peak_program_bytes = ledger.peak_program_bytes.max(ledger.program_bytes);
+        TrackedProgram {
+            id,
+            bytes,
+            ledger: self.ledger.clone(),
+        }
+    }
+}
+
+/// Bounds concurrent GPU dispatches, queueing a limited number of callers behind them
+#[derive(Debug, Clone)]
+pub struct GpuDispatchLimiter {
+    dispatch: Arc<Semaphore>,
+    admission: Arc<Semaphore>,
+    max_concurrency: usize,
+}
+
+/// Held for the duration of one GPU dispatch
+#[derive(Debug)]
+pub struct GpuDispatchPermit {
+    _dispatch: OwnedSemaphorePermit,
+    _admission: OwnedSemaphorePermit,
+}
+
+impl GpuDispatchLimiter {
+    pub fn new(max_concurrency: usize, queue_depth: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+        Self {
+            dispatch: Arc::new(Semaphore::new(max_concurrency)),
+            admission: Arc::new(Semaphore::new(max_concurrency + queue_depth)),
+            max_concurrency,
+        }
+    }
+
+    /// Wait for a dispatch slot, failing with `GpuBusy` when the queue is already full
+    pub async fn acquire(&self) -> crate::Result<GpuDispatchPermit> {
+        let admission = self.admission.clone().try_acquire_owned().map_err(|_| {
+            AiRuntimeError::gpu_busy(format!(
+                "{} GPU dispatches running and the queue is full",
+                self.max_concurrency
+            ))
+        })?;
+        let dispatch = self
+            .dispatch
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| AiRuntimeError::internal(e.to_string()))?;
+        Ok(GpuDispatchPermit {
+            _dispatch: dispatch,
+            _admission: admission,
+        })
+    }
+
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Dispatches currently holding a permit
+    pub fn in_flight(&self) -> usize {
+        self.max_concurrency - self.dispatch.available_permits()
+    }
+}
+
+impl Default for GpuDispatchLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_GPU_CONCURRENCY, GPU_DISPATCH_QUEUE_DEPTH)
+    }
+}
 
 /// Bridge between AI runtime and GPU execution
 #[derive(Debug)]
 pub struct GpuExecutionBridge {
     scheduler: Arc<Mutex<Option<OptimizedGpuExecutionScheduler>>>,
     gpu_core: Option<Arc<GpuCore>>,
+    programs: ProgramTracker,
 }
 
 impl GpuExecutionBridge {
@@ -19,6 +158,30 @@ impl GpuExecutionBridge {
         Self {
             scheduler: Arc::new(Mutex::new(None)),
             gpu_core,
+            programs: ProgramTracker::default(),
+        }
+    }
+
+    /// Tracker whose programs count toward this bridge's `memory_report`
+    pub fn program_tracker(&self) -> ProgramTracker {
+        self.programs.clone()
+    }
+
+    /// Count `bytes` of program data against this bridge until the handle is dropped
+    pub fn track_program(&self, bytes: u64) -> TrackedProgram {
+        self.programs.track(bytes)
+    }
+
+    pub fn memory_report(&self) -> GpuMemoryReport {
+        let ledger = self.programs.ledger.lock().unwrap();
+        GpuMemoryReport {
+            tracked_programs: ledger.live.len(),
+            program_bytes: ledger.program_bytes,
+            peak_program_bytes: ledger.peak_program_bytes,
+            budget_bytes: self
+                .gpu_core
+                .as_ref()
+                .and_then(|_| crate::monitor::gpu_memory_total_bytes()),
         }
     }
 
@@ -46,6 +209,7 @@ impl GpuExecutionBridge {
 
         if let Some(scheduler) = &*scheduler_guard {
             let start_time = std::time::Instant::now();
+            let _program = self.track_program(std::mem::size_of_val(program) as u64);
 
             let result = scheduler.execute_program(program, max_cycles).await?;
             if result.metadata.error_code != ExecutionErrorCode::Success {
@@ -84,3 +248,82 @@ impl GpuExecutionBridge {
         rgba
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracked_programs_are_reported_until_dropped() {
+        let bridge = GpuExecutionBridge::new(None);
+        let before = bridge.memory_report();
+
+        let program = bridge.track_program(4096);
+        let during = bridge.memory_report();
+        assert_eq!(during.program_bytes, before.program_bytes + 4096);
+        assert_eq!(during.tracked_programs, before.tracked_programs + 1);
+
+        drop(program);
+        let after = bridge.memory_report();
+        assert_eq!(after.program_bytes, before.program_bytes);
+        assert_eq!(after.peak_program_bytes, 4096);
+        assert_eq!(after.budget_bytes, None);
+    }
+
+    #[test]
+    fn shared_tracker_reports_into_the_bridge() {
+        let bridge = GpuExecutionBridge::new(None);
+        let tracker = bridge.program_tracker();
+
+        let program = tracker.track(256);
+        assert_eq!(bridge.memory_report().program_bytes, 256);
+        assert_eq!(bridge.memory_report().tracked_programs, 1);
+        drop(program);
+        assert_eq!(bridge.memory_report().program_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn single_permit_serializes_dispatches() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let limiter = GpuDispatchLimiter::new(1, GPU_DISPATCH_QUEUE_DEPTH);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let dispatch = || {
+            let (limiter, running, peak) = (limiter.clone(), running.clone(), peak.clone());
+            tokio::spawn(async move {
+                let _permit = limiter.acquire().await.unwrap();
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        };
+        let (a, b) = (dispatch(), dispatch());
+        a.await.unwrap();
+        b.await.unwrap();
+
+        assert_eq!(peak.load(Ordering::SeqCst), 1);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn full_queue_is_rejected_as_busy() {
+        let limiter = GpuDispatchLimiter::new(1, 1);
+        let running = limiter.acquire().await.unwrap();
+        let queued = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire().await.map(drop) }
+        });
+        tokio::task::yield_now().await;
+
+        assert!(matches!(
+            limiter.acquire().await,
+            Err(AiRuntimeError::GpuBusy(_))
+        ));
+        drop(running);
+        queued.await.unwrap().unwrap();
+        assert!(limiter.acquire().await.is_ok());
+    }
+}
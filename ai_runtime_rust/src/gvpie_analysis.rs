@@ -3,10 +3,16 @@
 //! This module provides AI-powered analysis specifically tailored for GVPIe development,
 //! including GPU pattern detection, Pixel VM optimization, and architecture validation.
 
+use crate::gpu_bridge::GpuMemoryReport;
+use crate::pixel_vm::{op_mnemonic, OpcodeCounts};
 use crate::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GvpieAnalysisReport {
@@ -17,6 +23,128 @@ pub struct GvpieAnalysisReport {
     pub optimization_suggestions: Vec<OptimizationSuggestion>,
     pub security_findings: Vec<SecurityFinding>,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Counts over the full report; left untouched by `filtered`.
+    #[serde(default)]
+    pub summary: ReportSummary,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportSummary {
+    pub suggestions_by_priority: BTreeMap<Priority, usize>,
+    pub findings_by_severity: BTreeMap<SecuritySeverity, usize>,
+}
+
+impl ReportSummary {
+    pub fn new(suggestions: &[OptimizationSuggestion], findings: &[SecurityFinding]) -> Self {
+        let mut summary = Self::default();
+        for suggestion in suggestions {
+            *summary
+                .suggestions_by_priority
+                .entry(suggestion.priority.clone())
+                .or_default() += 1;
+        }
+        for finding in findings {
+            *summary
+                .findings_by_severity
+                .entry(finding.severity.clone())
+                .or_default() += 1;
+        }
+        summary
+    }
+}
+
+impl GvpieAnalysisReport {
+    /// Keep only suggestions at least as urgent as `min_priority` and findings
+    /// at least as severe as `min_severity`. `summary` still counts everything.
+    pub fn filtered(
+        mut self,
+        min_priority: Option<Priority>,
+        min_severity: Option<SecuritySeverity>,
+    ) -> Self {
+        if let Some(min) = min_priority {
+            self.optimization_suggestions.retain(|s| s.priority <= min);
+        }
+        if let Some(min) = min_severity {
+            self.security_findings.retain(|f| f.severity <= min);
+        }
+        self
+    }
+
+    /// Render optimization suggestions as GitHub-style review comments.
+    ///
+    /// Suggestions with a `code_location` become line-anchored comments; the
+    /// rest are folded into a single file-level summary comment.
+    pub fn to_review_comments(&self) -> Vec<ReviewComment> {
+        let mut comments = Vec::new();
+        let mut unanchored = Vec::new();
+
+        for suggestion in &self.optimization_suggestions {
+            let Some(location) = &suggestion.code_location else {
+                unanchored.push(suggestion);
+                continue;
+            };
+            let mut body = suggestion.description.clone();
+            if let Some(code) = &suggestion.suggested_code {
+                body.push_str("\n\n```suggestion\n");
+                body.push_str(code);
+                body.push_str("\n```");
+            }
+            comments.push(ReviewComment {
+                path: Some(location.file_path.clone()),
+                line: Some(location.line_start),
+                severity: suggestion.priority.clone(),
+                body,
+            });
+        }
+
+        if let Some(severity) = unanchored.iter().map(|s| &s.priority).min() {
+            let mut body = format!(
+                "{} suggestion(s) without a code location:",
+                unanchored.len()
+            );
+            for suggestion in &unanchored {
+                body.push_str(&format!(
+                    "\n- [{:?}] {}",
+                    suggestion.priority, suggestion.description
+                ));
+            }
+            comments.push(ReviewComment {
+                path: None,
+                line: None,
+                severity: severity.clone(),
+                body,
+            });
+        }
+
+        comments
+    }
+}
+
+/// A review comment derived from an `OptimizationSuggestion`. `path`/`line`
+/// are `None` for the file-level summary of unanchored suggestions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewComment {
+    pub path: Option<String>,
+    pub line: Option<u32>,
+    pub severity: Priority,
+    pub body: String,
+}
+
+/// Render review comments as a markdown document, one `##` section per comment.
+pub fn review_comments_to_markdown(comments: &[ReviewComment]) -> String {
+    let mut markdown = String::from("# Review comments\n");
+    for comment in comments {
+        let location = match (&comment.path, comment.line) {
+            (Some(path), Some(line)) => format!("{}:{}", path, line),
+            (Some(path), None) => path.clone(),
+            _ => "General".to_string(),
+        };
+        markdown.push_str(&format!(
+            "\n## [{:?}] {}\n\n{}\n",
+            comment.severity, location, comment.body
+        ));
+    }
+    markdown
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,11 +163,24 @@ pub struct GpuAnalysis {
     pub gpu_utilization_score: f32,
     pub wgsl_optimization_opportunities: Vec<WgslOptimization>,
     pub compute_shader_efficiency: f32,
+    /// Program bytes in flight on the GPU at analysis time, when a runtime supplied them.
+    /// Reported as-is; `memory_usage_patterns` stays a static estimate
+    #[serde(default)]
+    pub program_memory: Option<GpuMemoryReport>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PixelVmAnalysis {
+    /// Percent of executed steps per opcode mnemonic, measured from recent executions once there
+    /// have been any, else a static estimate
     pub instruction_frequency: HashMap<String, u32>,
+    /// Whether `instruction_frequency` was measured rather than the static estimate
+    #[serde(default)]
+    pub instruction_frequency_measured: bool,
+    /// Measured steps left out of `instruction_frequency`: the executor doesn't report which
+    /// instructions a run that jumped went through
+    #[serde(default)]
+    pub unattributed_steps: u64,
     pub execution_path_analysis: Vec<ExecutionPath>,
     pub vm_performance_score: f32,
     pub bytecode_optimization_opportunities: Vec<BytecodeOptimization>,
@@ -55,6 +196,49 @@ pub struct PerformanceInsights {
     pub benchmark_comparisons: Vec<BenchmarkComparison>,
 }
 
+/// One suggestion or finding, emitted while a full analysis is running
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "item", rename_all = "snake_case")]
+pub enum AnalysisItem {
+    Suggestion(OptimizationSuggestion),
+    Finding(SecurityFinding),
+}
+
+/// A full analysis raced against a deadline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundedAnalysis {
+    /// The complete report, or `None` when the deadline passed first
+    pub report: Option<GvpieAnalysisReport>,
+    /// Suggestions and findings emitted before the deadline cut the analysis short
+    pub partial: Vec<AnalysisItem>,
+    pub truncated: bool,
+}
+
+/// Run a streaming analysis, abandoning it once `deadline` elapses and keeping
+/// whatever items it had emitted by then
+pub async fn analyze_within<F, Fut>(deadline: Duration, analysis: F) -> Result<BoundedAnalysis>
+where
+    F: FnOnce(Box<dyn FnMut(AnalysisItem) + Send>) -> Fut,
+    Fut: Future<Output = Result<GvpieAnalysisReport>>,
+{
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let sink = collected.clone();
+    let run = analysis(Box::new(move |item| sink.lock().unwrap().push(item)));
+
+    match tokio::time::timeout(deadline, run).await {
+        Ok(report) => Ok(BoundedAnalysis {
+            report: Some(report?),
+            partial: Vec::new(),
+            truncated: false,
+        }),
+        Err(_) => Ok(BoundedAnalysis {
+            report: None,
+            partial: std::mem::take(&mut *collected.lock().unwrap()),
+            truncated: true,
+        }),
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OptimizationSuggestion {
     pub category: OptimizationCategory,
@@ -198,7 +382,8 @@ pub enum OptimizationCategory {
     Maintainability,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Ordered most to least urgent, so `Critical < Low`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum Priority {
     Critical,
     High,
@@ -215,7 +400,8 @@ pub enum Complexity {
     Expert,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Ordered most to least severe, so `Critical < Info`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum SecuritySeverity {
     Critical,
     High,
@@ -264,19 +450,84 @@ pub enum BottleneckType {
 #[derive(Debug)]
 pub struct GvpieAnalyzer {
     workspace_root: PathBuf,
-    analysis_cache: HashMap<String, GvpieAnalysisReport>,
+    analysis_cache: Mutex<HashMap<String, GvpieAnalysisReport>>,
+    /// Held for the duration of a full analysis so concurrent callers queue
+    /// behind it and reuse its result instead of recomputing.
+    full_analysis: tokio::sync::Mutex<()>,
+    full_analysis_runs: AtomicUsize,
+    program_memory: Mutex<Option<GpuMemoryReport>>,
+    opcode_counts: Mutex<OpcodeCounts>,
 }
 
 impl GvpieAnalyzer {
     pub fn new<P: AsRef<Path>>(workspace_root: P) -> Self {
         Self {
             workspace_root: workspace_root.as_ref().to_path_buf(),
-            analysis_cache: HashMap::new(),
+            analysis_cache: Mutex::new(HashMap::new()),
+            full_analysis: tokio::sync::Mutex::new(()),
+            full_analysis_runs: AtomicUsize::new(0),
+            program_memory: Mutex::new(None),
+            opcode_counts: Mutex::new(OpcodeCounts::default()),
         }
     }
 
+    /// Number of full codebase analyses actually computed (shared results excluded)
+    pub fn full_analysis_runs(&self) -> usize {
+        self.full_analysis_runs.load(Ordering::Acquire)
+    }
+
+    /// Use the bridge's dispatched program bytes in subsequent GPU analyses
+    pub fn record_program_memory(&self, report: GpuMemoryReport) {
+        *self.program_memory.lock().unwrap() = Some(report);
+    }
+
+    /// Use executed opcode counts for the instruction frequency of subsequent Pixel VM analyses
+    pub fn record_opcode_counts(&self, counts: OpcodeCounts) {
+        *self.opcode_counts.lock().unwrap() = counts;
+    }
+
+    fn cached(&self, key: &str) -> Option<GvpieAnalysisReport> {
+        self.analysis_cache.lock().unwrap().get(key).cloned()
+    }
+
     /// Analyze the entire GVPIe codebase and provide comprehensive insights
-    pub async fn analyze_gvpie_codebase(&mut self) -> Result<GvpieAnalysisReport> {
+    ///
+    /// Calls that arrive while an analysis is in flight wait for it and share
+    /// its report rather than starting another one.
+    pub async fn analyze_gvpie_codebase(&self) -> Result<GvpieAnalysisReport> {
+        self.analyze_gvpie_codebase_streaming(|_| {}).await
+    }
+
+    /// Like `analyze_gvpie_codebase`, but hands each suggestion and finding to
+    /// `on_item` as soon as its analysis phase produces it
+    pub async fn analyze_gvpie_codebase_streaming<F>(
+        &self,
+        mut on_item: F,
+    ) -> Result<GvpieAnalysisReport>
+    where
+        F: FnMut(AnalysisItem) + Send,
+    {
+        let runs_at_arrival = self.full_analysis_runs();
+        let _in_flight = self.full_analysis.lock().await;
+        if self.full_analysis_runs() != runs_at_arrival {
+            if let Some(report) = self.cached("full_analysis") {
+                report
+                    .optimization_suggestions
+                    .iter()
+                    .cloned()
+                    .map(AnalysisItem::Suggestion)
+                    .chain(
+                        report
+                            .security_findings
+                            .iter()
+                            .cloned()
+                            .map(AnalysisItem::Finding),
+                    )
+                    .for_each(&mut on_item);
+                return Ok(report);
+            }
+        }
+
         tracing::info!("Starting comprehensive GVPIe codebase analysis");
 
         let architecture_analysis = self.analyze_architecture().await?;
@@ -290,13 +541,22 @@ impl GvpieAnalyzer {
                 &pixel_vm_analysis,
             )
             .await?;
+        optimization_suggestions
+            .iter()
+            .cloned()
+            .for_each(|suggestion| on_item(AnalysisItem::Suggestion(suggestion)));
         let security_findings = self.analyze_security().await?;
+        security_findings
+            .iter()
+            .cloned()
+            .for_each(|finding| on_item(AnalysisItem::Finding(finding)));
 
         let report = GvpieAnalysisReport {
             architecture_analysis,
             gpu_analysis,
             pixel_vm_analysis,
             performance_insights,
+            summary: ReportSummary::new(&optimization_suggestions, &security_findings),
             optimization_suggestions,
             security_findings,
             timestamp: chrono::Utc::now(),
@@ -304,7 +564,10 @@ impl GvpieAnalyzer {
 
         // Cache the report
         self.analysis_cache
+            .lock()
+            .unwrap()
             .insert("full_analysis".to_string(), report.clone());
+        self.full_analysis_runs.fetch_add(1, Ordering::Release);
 
         tracing::info!("GVPIe codebase analysis completed");
         Ok(report)
@@ -312,7 +575,7 @@ impl GvpieAnalyzer {
 
     /// Analyze a specific component or file
     pub async fn analyze_component<P: AsRef<Path>>(
-        &mut self,
+        &self,
         component_path: P,
     ) -> Result<GvpieAnalysisReport> {
         let path = component_path.as_ref();
@@ -320,9 +583,9 @@ impl GvpieAnalyzer {
 
         // Check cache first
         let cache_key = path.to_string_lossy().to_string();
-        if let Some(cached_report) = self.analysis_cache.get(&cache_key) {
+        if let Some(cached_report) = self.cached(&cache_key) {
             if cached_report.timestamp > chrono::Utc::now() - chrono::Duration::minutes(30) {
-                return Ok(cached_report.clone());
+                return Ok(cached_report);
             }
         }
 
@@ -330,7 +593,10 @@ impl GvpieAnalyzer {
         let report = self.analyze_component_internal(path).await?;
 
         // Cache the result
-        self.analysis_cache.insert(cache_key, report.clone());
+        self.analysis_cache
+            .lock()
+            .unwrap()
+            .insert(cache_key, report.clone());
 
         Ok(report)
     }
@@ -340,30 +606,24 @@ impl GvpieAnalyzer {
         &self,
         changed_files: &[PathBuf],
     ) -> Result<Vec<OptimizationSuggestion>> {
-        let mut suggestions = Vec::new();
-
-        for file_path in changed_files {
-            let file_suggestions = self.analyze_file_changes(file_path).await?;
-            suggestions.extend(file_suggestions);
-        }
-
-        // Sort by priority and impact
-        suggestions.sort_by(|a, b| match (a.priority.clone(), b.priority.clone()) {
-            (Priority::Critical, Priority::Critical) => a
-                .estimated_impact
-                .performance_gain
-                .partial_cmp(&b.estimated_impact.performance_gain)
-                .unwrap_or(std::cmp::Ordering::Equal),
-            (Priority::Critical, _) => std::cmp::Ordering::Less,
-            (_, Priority::Critical) => std::cmp::Ordering::Greater,
-            (Priority::High, Priority::High) => a
-                .estimated_impact
-                .performance_gain
-                .partial_cmp(&b.estimated_impact.performance_gain)
-                .unwrap_or(std::cmp::Ordering::Equal),
-            (Priority::High, _) => std::cmp::Ordering::Less,
-            (_, Priority::High) => std::cmp::Ordering::Greater,
-            _ => std::cmp::Ordering::Equal,
+        let per_file = futures::future::try_join_all(
+            changed_files
+                .iter()
+                .map(|file_path| self.analyze_file_changes(file_path)),
+        )
+        .await?;
+        let mut suggestions: Vec<_> = per_file.into_iter().flatten().collect();
+
+        // Files are analyzed concurrently, so order by a stable key rather than completion
+        suggestions.sort_by(|a, b| {
+            let location = |s: &OptimizationSuggestion| {
+                s.code_location
+                    .as_ref()
+                    .map(|loc| (loc.file_path.clone(), loc.line_start))
+            };
+            a.priority
+                .cmp(&b.priority)
+                .then_with(|| location(a).cmp(&location(b)))
         });
 
         Ok(suggestions)
@@ -498,23 +758,32 @@ impl GvpieAnalyzer {
             expected_speedup: 1.3,
         }];
 
+        let program_memory = self.program_memory.lock().unwrap().clone();
+
         Ok(GpuAnalysis {
             shader_complexity,
             memory_usage_patterns,
             gpu_utilization_score: 0.78,
             wgsl_optimization_opportunities,
             compute_shader_efficiency: 0.85,
+            program_memory,
         })
     }
 
     async fn analyze_pixel_vm(&self) -> Result<PixelVmAnalysis> {
         // Analyze Pixel VM performance and optimization opportunities
-        let mut instruction_frequency = HashMap::new();
-        instruction_frequency.insert("PUTPIX".to_string(), 45);
-        instruction_frequency.insert("FILL".to_string(), 20);
-        instruction_frequency.insert("LOAD".to_string(), 15);
-        instruction_frequency.insert("STORE".to_string(), 12);
-        instruction_frequency.insert("JUMP".to_string(), 8);
+        let measured = self.opcode_counts.lock().unwrap().clone();
+        let instruction_frequency = if measured.is_empty() {
+            let mut instruction_frequency = HashMap::new();
+            instruction_frequency.insert("PUTPIX".to_string(), 45);
+            instruction_frequency.insert("FILL".to_string(), 20);
+            instruction_frequency.insert("LOAD".to_string(), 15);
+            instruction_frequency.insert("STORE".to_string(), 12);
+            instruction_frequency.insert("JUMP".to_string(), 8);
+            instruction_frequency
+        } else {
+            instruction_percentages(&measured.executed)
+        };
 
         let execution_path_analysis = vec![ExecutionPath {
             path_id: "main_render_loop".to_string(),
@@ -544,6 +813,8 @@ impl GvpieAnalyzer {
 
         Ok(PixelVmAnalysis {
             instruction_frequency,
+            instruction_frequency_measured: !measured.is_empty(),
+            unattributed_steps: measured.unattributed_steps,
             execution_path_analysis,
             vm_performance_score: 0.88,
             bytecode_optimization_opportunities,
@@ -805,9 +1076,12 @@ pub trait GpuOperation {
                 gpu_utilization_score: 0.75,
                 wgsl_optimization_opportunities: Vec::new(),
                 compute_shader_efficiency: 0.8,
+                program_memory: None,
             },
             pixel_vm_analysis: PixelVmAnalysis {
                 instruction_frequency: HashMap::new(),
+                instruction_frequency_measured: false,
+                unattributed_steps: 0,
                 execution_path_analysis: Vec::new(),
                 vm_performance_score: 0.85,
                 bytecode_optimization_opportunities: Vec::new(),
@@ -825,6 +1099,7 @@ pub trait GpuOperation {
                 },
                 benchmark_comparisons: Vec::new(),
             },
+            summary: ReportSummary::new(&suggestions, &[]),
             optimization_suggestions: suggestions,
             security_findings: Vec::new(),
             timestamp: chrono::Utc::now(),
@@ -884,3 +1159,294 @@ pub trait GpuOperation {
         Ok(suggestions)
     }
 }
+
+/// Share of `counts`, in whole percent, per opcode mnemonic
+fn instruction_percentages(counts: &HashMap<gvpie_core::PixelOp, u64>) -> HashMap<String, u32> {
+    let total: u64 = counts.values().sum();
+    counts
+        .iter()
+        .map(|(&op, count)| {
+            let percent = (count * 100 + total / 2) / total.max(1);
+            (op_mnemonic(op).to_string(), percent as u32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(priority: Priority, location: Option<(&str, u32)>) -> OptimizationSuggestion {
+        OptimizationSuggestion {
+            category: OptimizationCategory::Performance,
+            priority,
+            description: "Batch the dispatches".to_string(),
+            code_location: location.map(|(file_path, line)| CodeLocation {
+                file_path: file_path.to_string(),
+                line_start: line,
+                line_end: line,
+                column_start: None,
+                column_end: None,
+            }),
+            estimated_impact: ImpactEstimate {
+                performance_gain: 0.1,
+                memory_reduction: 0.0,
+                maintainability_improvement: 0.0,
+            },
+            implementation_complexity: Complexity::Simple,
+            suggested_code: Some("queue.submit(batch);".to_string()),
+        }
+    }
+
+    fn finding(severity: SecuritySeverity) -> SecurityFinding {
+        SecurityFinding {
+            severity,
+            category: SecurityCategory::InputValidation,
+            description: "Unchecked buffer length".to_string(),
+            location: CodeLocation {
+                file_path: "src/api.rs".to_string(),
+                line_start: 1,
+                line_end: 1,
+                column_start: None,
+                column_end: None,
+            },
+            remediation: "Validate lengths".to_string(),
+        }
+    }
+
+    async fn report_with(suggestions: Vec<OptimizationSuggestion>) -> GvpieAnalysisReport {
+        let dir = tempfile::tempdir().unwrap();
+        let mut report = GvpieAnalyzer::new(dir.path())
+            .analyze_gvpie_codebase()
+            .await
+            .unwrap();
+        report.optimization_suggestions = suggestions;
+        report
+    }
+
+    #[tokio::test]
+    async fn slow_analysis_is_truncated_at_the_deadline() {
+        let bounded = analyze_within(Duration::from_millis(20), |mut on_item| async move {
+            on_item(AnalysisItem::Suggestion(suggestion(Priority::High, None)));
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(report_with(Vec::new()).await)
+        })
+        .await
+        .unwrap();
+
+        assert!(bounded.truncated);
+        assert!(bounded.report.is_none());
+        assert_eq!(bounded.partial.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn analysis_within_the_deadline_returns_the_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = GvpieAnalyzer::new(dir.path());
+        let bounded = analyze_within(Duration::from_secs(30), |on_item| {
+            analyzer.analyze_gvpie_codebase_streaming(on_item)
+        })
+        .await
+        .unwrap();
+
+        assert!(!bounded.truncated);
+        assert!(bounded.report.is_some());
+        assert!(bounded.partial.is_empty());
+    }
+
+    #[tokio::test]
+    async fn instruction_frequency_prefers_measured_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = GvpieAnalyzer::new(dir.path());
+        let fallback = analyzer.analyze_pixel_vm().await.unwrap();
+        assert_eq!(fallback.instruction_frequency["PUTPIX"], 45);
+        assert!(!fallback.instruction_frequency_measured);
+
+        analyzer.record_opcode_counts(OpcodeCounts {
+            executed: HashMap::from([
+                (gvpie_core::PixelOp::FILL, 3),
+                (gvpie_core::PixelOp::HALT, 1),
+            ]),
+            unattributed_steps: 7,
+        });
+        let measured = analyzer.analyze_pixel_vm().await.unwrap();
+        assert_eq!(
+            measured.instruction_frequency,
+            HashMap::from([("FILL".to_string(), 75), ("HALT".to_string(), 25)])
+        );
+        assert!(measured.instruction_frequency_measured);
+        assert_eq!(measured.unattributed_steps, 7);
+    }
+
+    #[tokio::test]
+    async fn located_suggestion_becomes_line_comment() {
+        let report = report_with(vec![suggestion(
+            Priority::High,
+            Some(("src/gpu_bridge.rs", 42)),
+        )])
+        .await;
+
+        let comments = report.to_review_comments();
+        assert_eq!(comments.len(), 1);
+        assert_eq!(comments[0].path.as_deref(), Some("src/gpu_bridge.rs"));
+        assert_eq!(comments[0].line, Some(42));
+        assert!(matches!(comments[0].severity, Priority::High));
+        assert!(comments[0]
+            .body
+            .contains("```suggestion\nqueue.submit(batch);\n```"));
+    }
+
+    #[tokio::test]
+    async fn unlocated_suggestions_share_a_summary() {
+        let report = report_with(vec![
+            suggestion(Priority::Low, None),
+            suggestion(Priority::Medium, Some(("src/lib.rs", 1))),
+            suggestion(Priority::Low, None),
+        ])
+        .await;
+
+        let comments = report.to_review_comments();
+        assert_eq!(comments.len(), 2);
+        let summary = &comments[1];
+        assert!(summary.path.is_none() && summary.line.is_none());
+        assert!(summary.body.starts_with("2 suggestion(s)"));
+
+        let markdown = review_comments_to_markdown(&comments);
+        assert!(markdown.starts_with("# Review comments\n"));
+        assert!(markdown.contains("\n## [Medium] src/lib.rs:1\n"));
+        assert!(markdown.contains("\n## [Low] General\n\n2 suggestion(s)"));
+    }
+
+    #[tokio::test]
+    async fn filtering_drops_lower_priority_items() {
+        let mut report = report_with(vec![
+            suggestion(Priority::Critical, None),
+            suggestion(Priority::High, None),
+            suggestion(Priority::Medium, None),
+            suggestion(Priority::Low, None),
+        ])
+        .await;
+        report.security_findings = vec![
+            finding(SecuritySeverity::High),
+            finding(SecuritySeverity::Low),
+            finding(SecuritySeverity::Info),
+        ];
+        report.summary =
+            ReportSummary::new(&report.optimization_suggestions, &report.security_findings);
+        let unfiltered = report.summary.clone();
+
+        let filtered = report.filtered(Some(Priority::High), Some(SecuritySeverity::Medium));
+        let priorities: Vec<_> = filtered
+            .optimization_suggestions
+            .iter()
+            .map(|s| s.priority.clone())
+            .collect();
+        assert_eq!(priorities, vec![Priority::Critical, Priority::High]);
+        assert_eq!(filtered.security_findings.len(), 1);
+
+        assert_eq!(filtered.summary, unfiltered);
+        assert_eq!(filtered.summary.suggestions_by_priority[&Priority::Low], 1);
+        assert_eq!(
+            filtered.summary.findings_by_severity[&SecuritySeverity::Info],
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn change_suggestions_have_a_stable_order() {
+        let analyzer = GvpieAnalyzer::new(".");
+        let files: Vec<PathBuf> = [
+            "src/shaders/z_pass.wgsl",
+            "src/gpu/scheduler.rs",
+            "src/shaders/a_pass.wgsl",
+            "src/gpu/bridge.rs",
+            "README.md",
+        ]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+        let expected = analyzer
+            .suggest_improvements_for_changes(&files)
+            .await
+            .unwrap();
+        let order = |suggestions: &[OptimizationSuggestion]| -> Vec<(Priority, String)> {
+            suggestions
+                .iter()
+                .map(|s| {
+                    let location = s.code_location.as_ref().unwrap();
+                    (s.priority.clone(), location.file_path.clone())
+                })
+                .collect()
+        };
+        assert_eq!(
+            order(&expected),
+            vec![
+                (Priority::High, "src/shaders/a_pass.wgsl".to_string()),
+                (Priority::High, "src/shaders/z_pass.wgsl".to_string()),
+                (Priority::Medium, "src/gpu/bridge.rs".to_string()),
+                (Priority::Medium, "src/gpu/scheduler.rs".to_string()),
+            ]
+        );
+
+        let mut shuffled = files.clone();
+        for _ in 0..files.len() {
+            shuffled.rotate_left(2);
+            let again = analyzer
+                .suggest_improvements_for_changes(&shuffled)
+                .await
+                .unwrap();
+            assert_eq!(order(&again), order(&expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn streaming_emits_every_reported_item() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = GvpieAnalyzer::new(dir.path());
+
+        for _ in 0..2 {
+            let mut streamed = Vec::new();
+            let report = analyzer
+                .analyze_gvpie_codebase_streaming(|item| streamed.push(item))
+                .await
+                .unwrap();
+
+            let suggestions = streamed
+                .iter()
+                .filter(|item| matches!(item, AnalysisItem::Suggestion(_)))
+                .count();
+            assert!(suggestions >= report.optimization_suggestions.len());
+            assert!(streamed.len() - suggestions >= report.security_findings.len());
+            assert!(!streamed.is_empty());
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_full_analyses_share_one_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let analyzer = std::sync::Arc::new(GvpieAnalyzer::new(dir.path()));
+
+        // Park both callers behind an in-flight analysis before either can start.
+        let in_flight = analyzer.full_analysis.lock().await;
+        let first = tokio::spawn({
+            let analyzer = analyzer.clone();
+            async move { analyzer.analyze_gvpie_codebase().await }
+        });
+        let second = tokio::spawn({
+            let analyzer = analyzer.clone();
+            async move { analyzer.analyze_gvpie_codebase().await }
+        });
+        tokio::task::yield_now().await;
+        drop(in_flight);
+
+        let first = first.await.unwrap().unwrap();
+        let second = second.await.unwrap().unwrap();
+        assert_eq!(analyzer.full_analysis_runs(), 1);
+        assert_eq!(first.timestamp, second.timestamp);
+
+        // A later, non-overlapping call recomputes.
+        analyzer.analyze_gvpie_codebase().await.unwrap();
+        assert_eq!(analyzer.full_analysis_runs(), 2);
+    }
+}
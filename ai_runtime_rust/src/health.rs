@@ -0,0 +1,95 @@
+//! Liveness checks for the AI Runtime
+//!
+//! Backs `GET /health`: each component reports its own status, and the
+//! overall status is the worst of them. Only critical components can take
+//! the runtime down; the rest degrade it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Ok,
+    Disabled,
+    Degraded,
+    Down,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub status: HealthStatus,
+    pub critical: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl ComponentHealth {
+    pub fn ok(critical: bool) -> Self {
+        Self {
+            status: HealthStatus::Ok,
+            critical,
+            detail: None,
+        }
+    }
+
+    pub fn disabled(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Disabled,
+            critical: false,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// A failed check: `Down` if the component is critical, `Degraded` otherwise.
+    pub fn failed(critical: bool, detail: impl Into<String>) -> Self {
+        Self {
+            status: if critical {
+                HealthStatus::Down
+            } else {
+                HealthStatus::Degraded
+            },
+            critical,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub components: BTreeMap<String, ComponentHealth>,
+}
+
+impl HealthReport {
+    pub fn new(components: BTreeMap<String, ComponentHealth>) -> Self {
+        let status = components
+            .values()
+            .map(|c| match c.status {
+                HealthStatus::Disabled => HealthStatus::Ok,
+                status => status,
+            })
+            .max()
+            .unwrap_or(HealthStatus::Ok);
+        Self { status, components }
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.status == HealthStatus::Down
+    }
+}
+
+/// Check that `dir` exists (creating it if needed) and accepts new files.
+pub fn check_writable_dir(dir: &Path, critical: bool) -> ComponentHealth {
+    let probe = dir.join(".gvpie_health_probe");
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => ComponentHealth::ok(critical),
+        Err(e) => {
+            ComponentHealth::failed(critical, format!("{} not writable: {}", dir.display(), e))
+        }
+    }
+}
@@ -3,32 +3,56 @@ pub mod cartridges;
 pub mod config;
 pub mod database;
 pub mod errors;
+pub mod execution_cache;
 pub mod gpu_bridge;
 pub mod gvpie_analysis;
+pub mod health;
 pub mod logging;
 pub mod models;
 pub mod monitor;
 pub mod pixel_vm;
+pub mod selftest;
 
 pub use api::SystemStatus;
-pub use cartridges::Cartridge;
+pub use cartridges::{Cartridge, CodeFormat};
 pub use database::{
-    DecisionRecord, EventRecord, ExperienceDB, PatternAnalysis, SystemMetricsRecord, TrendAnalysis,
+    CartridgeExecutionRecord, DecisionRecord, EventRecord, ExperienceDB, PatternAnalysis,
+    RuntimeStats, SystemMetricsRecord, TrendAnalysis,
 };
 pub use errors::{AiRuntimeError, Result};
+pub use gpu_bridge::GpuMemoryReport;
 pub use gvpie_analysis::{
-    GvpieAnalysisReport, GvpieAnalyzer, OptimizationSuggestion, PerformanceInsights,
+    AnalysisItem, GvpieAnalysisReport, GvpieAnalyzer, OptimizationSuggestion, PerformanceInsights,
 };
+pub use health::{ComponentHealth, HealthReport, HealthStatus};
 pub use logging::{IncidentSeverity, LogSeverity, StructuredLogger};
-pub use monitor::{SystemMetrics, SystemMonitor};
+pub use monitor::{SystemMetrics, SystemMonitor, TrackedMetric};
+pub use selftest::{SelftestOutcome, SelftestReport};
 
-use std::{path::PathBuf, sync::Arc};
-use tokio::sync::RwLock;
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{OwnedRwLockWriteGuard, RwLock};
 
+use execution_cache::{ExecutionCache, ExecutionKey};
 use gpu_bridge::GpuExecutionBridge;
 use gvpie_core::PixelInstruction;
 
-pub use pixel_vm::{ExecutionBackend, PixelProgramRequest, PixelProgramResponse};
+pub use pixel_vm::{
+    canvas_fingerprint, canvas_region, canvas_to_ascii, differing_pixels, BackendDiff,
+    CanvasRegion, ExecutionBackend, PixelProgramRequest, PixelProgramResponse,
+    CANVAS_FINGERPRINT_HEADER, CANVAS_REGION_HEADER,
+};
+
+/// Executions kept per cartridge in the history table
+pub const MAX_EXECUTION_HISTORY: usize = 100;
+
+/// Cartridge execution results kept in the LRU cache
+pub const EXECUTION_CACHE_CAPACITY: usize = 128;
+
+/// Widest window, in hours, `AiRuntime::stats` accepts
+pub const MAX_STATS_HOURS: i64 = 24 * 365;
 
 #[derive(Debug)]
 pub struct AiRuntime {
@@ -36,13 +60,23 @@ pub struct AiRuntime {
     gpu_core: Option<Arc<gvpie_core::GpuCore>>,
     pixel_vm: pixel_vm::PixelVmRuntime,
     cartridge_manager: Arc<RwLock<cartridges::CartridgeManager>>,
+    execution_cache: Mutex<ExecutionCache<ExecutionResult>>,
     gpu_bridge: GpuExecutionBridge,
-    gvpie_analyzer: Arc<RwLock<gvpie_analysis::GvpieAnalyzer>>,
-    // TODO: Add database, monitoring, etc.
+    gvpie_analyzer: Arc<gvpie_analysis::GvpieAnalyzer>,
+    database: Arc<ExperienceDB>,
+    log_dir: PathBuf,
+    config: config::Config,
+    opcode_counts: pixel_vm::OpcodeCounter,
+    // TODO: Add monitoring, etc.
 }
 
 impl AiRuntime {
     pub async fn new() -> Result<Self> {
+        Self::with_config(config::Config::load()?).await
+    }
+
+    /// Build a runtime from an already loaded configuration
+    pub async fn with_config(config: config::Config) -> Result<Self> {
         // Initialize GPU core (may fail if no GPU available)
         #[cfg(feature = "gpu")]
         let gpu_core = if std::env::var("GVPIE_DISABLE_GPU").is_ok() {
@@ -51,7 +85,7 @@ impl AiRuntime {
             match gvpie_core::GpuCore::new().await {
                 Ok(core) => Some(Arc::new(core)),
                 Err(e) => {
-                    println!("⚠️  GPU not available: {}", e);
+                    tracing::warn!(error = %e, "GPU not available");
                     None
                 }
             }
@@ -60,14 +94,25 @@ impl AiRuntime {
         #[cfg(not(feature = "gpu"))]
         let gpu_core = None;
 
-        let cartridge_manager = cartridges::CartridgeManager::new(cartridge_storage_path())?;
+        let cartridge_manager = tokio::task::spawn_blocking(|| {
+            cartridges::CartridgeManager::new(cartridge_storage_path())
+        })
+        .await
+        .map_err(|err| AiRuntimeError::internal(err.to_string()))??;
+
+        let gpu_bridge = GpuExecutionBridge::new(gpu_core.clone());
 
         #[cfg(feature = "gpu")]
         let pixel_vm = pixel_vm::PixelVmRuntime::new(gpu_core.clone());
         #[cfg(not(feature = "gpu"))]
         let pixel_vm = pixel_vm::PixelVmRuntime::new(None);
-
-        let gpu_bridge = GpuExecutionBridge::new(gpu_core.clone());
+        let pixel_vm = pixel_vm
+            .with_auto_gpu_threshold(auto_gpu_threshold())
+            .with_gpu_dispatch_limiter(gpu_bridge::GpuDispatchLimiter::new(
+                max_gpu_concurrency(),
+                gpu_bridge::GPU_DISPATCH_QUEUE_DEPTH,
+            ))
+            .with_program_tracker(gpu_bridge.program_tracker());
 
         // Initialize GPU bridge if available
         if gpu_bridge.is_gpu_available() {
@@ -81,13 +126,32 @@ impl AiRuntime {
         let workspace_root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
         let gvpie_analyzer = gvpie_analysis::GvpieAnalyzer::new(workspace_root);
 
-        Ok(Self {
+        let database = ExperienceDB::with_config(config.database_path(), &config.database).await?;
+
+        let runtime = Self {
             gpu_core,
             pixel_vm,
             cartridge_manager: Arc::new(RwLock::new(cartridge_manager)),
+            execution_cache: Mutex::new(ExecutionCache::new(EXECUTION_CACHE_CAPACITY)),
             gpu_bridge,
-            gvpie_analyzer: Arc::new(RwLock::new(gvpie_analyzer)),
-        })
+            gvpie_analyzer: Arc::new(gvpie_analyzer),
+            database: Arc::new(database),
+            log_dir: log_dir(),
+            config,
+            opcode_counts: pixel_vm::OpcodeCounter::default(),
+        };
+
+        let startup = runtime.startup_report();
+        tracing::info!(
+            version = %startup.version,
+            gpu = %startup.gpu,
+            features = ?startup.features,
+            db_path = %startup.db_path.display(),
+            log_dir = %startup.log_dir.display(),
+            pixel_backends = ?startup.pixel_backends,
+            "AI runtime started"
+        );
+        Ok(runtime)
     }
 
     #[cfg(feature = "gpu")]
@@ -100,11 +164,170 @@ impl AiRuntime {
         false
     }
 
+    pub fn database(&self) -> &ExperienceDB {
+        &self.database
+    }
+
+    /// System monitor sampling the optional metrics enabled in the config's `monitor.metrics`
+    pub fn system_monitor(&self) -> SystemMonitor {
+        SystemMonitor::from_config(&self.config.monitor)
+    }
+
+    /// Lightweight liveness checks for the database, GPU, and writable directories
+    pub async fn health_check(&self) -> HealthReport {
+        let mut components = std::collections::BTreeMap::new();
+
+        components.insert(
+            "database".to_string(),
+            match self.database.ping().await {
+                Ok(()) => ComponentHealth::ok(true),
+                Err(e) => ComponentHealth::failed(true, e.to_string()),
+            },
+        );
+
+        let gpu_expected = cfg!(feature = "gpu") && std::env::var("GVPIE_DISABLE_GPU").is_err();
+        components.insert(
+            "gpu".to_string(),
+            if !gpu_expected {
+                ComponentHealth::disabled("GPU disabled for this runtime")
+            } else if self.gpu_available() {
+                ComponentHealth::ok(false)
+            } else {
+                ComponentHealth::failed(false, "GPU expected but no GPU core available")
+            },
+        );
+
+        components.insert(
+            "cartridges".to_string(),
+            health::check_writable_dir(&cartridge_storage_path(), true),
+        );
+        components.insert(
+            "logs".to_string(),
+            health::check_writable_dir(&self.log_dir, false),
+        );
+
+        HealthReport::new(components)
+    }
+
+    /// Run the sample program on every available backend and compare the results, then
+    /// analyze the sample component. Selftest runs are not counted in the opcode statistics.
+    pub async fn selftest(&self) -> selftest::SelftestReport {
+        use selftest::{CheckStatus, SelftestCheck};
+
+        let request = |backend| PixelProgramRequest {
+            program: selftest::sample_program(),
+            backend,
+            max_cycles: selftest::SELFTEST_MAX_CYCLES,
+            canvas_width: selftest::SELFTEST_CANVAS_SIZE,
+            canvas_height: selftest::SELFTEST_CANVAS_SIZE,
+        };
+        let mut checks = Vec::new();
+
+        let cpu = match self
+            .pixel_vm
+            .execute_program(request(ExecutionBackend::Cpu))
+            .await
+        {
+            Ok(response) if response.success => {
+                checks.push(SelftestCheck::new(
+                    "cpu_execution",
+                    CheckStatus::Pass,
+                    format!(
+                        "{} cycles, canvas {:016x}",
+                        response.cycles_executed,
+                        canvas_fingerprint(&response.canvas_data)
+                    ),
+                ));
+                Some(response)
+            }
+            Ok(response) => {
+                checks.push(SelftestCheck::new(
+                    "cpu_execution",
+                    CheckStatus::Fail,
+                    response.error.unwrap_or_default(),
+                ));
+                None
+            }
+            Err(e) => {
+                checks.push(SelftestCheck::new(
+                    "cpu_execution",
+                    CheckStatus::Fail,
+                    e.to_string(),
+                ));
+                None
+            }
+        };
+
+        if !self.gpu_available() {
+            checks.push(SelftestCheck::new(
+                "gpu_execution",
+                CheckStatus::Skipped,
+                "no GPU core available; running CPU-only",
+            ));
+        } else {
+            let gpu = self
+                .pixel_vm
+                .execute_program(request(ExecutionBackend::Gpu))
+                .await;
+            checks.push(match (&cpu, gpu) {
+                (_, Err(e)) => {
+                    SelftestCheck::new("gpu_execution", CheckStatus::Fail, e.to_string())
+                }
+                (_, Ok(response)) if !response.success => SelftestCheck::new(
+                    "gpu_execution",
+                    CheckStatus::Fail,
+                    response.error.unwrap_or_default(),
+                ),
+                (None, Ok(_)) => SelftestCheck::new(
+                    "gpu_execution",
+                    CheckStatus::Fail,
+                    "GPU ran but there is no CPU result to compare against",
+                ),
+                (Some(cpu), Ok(gpu)) => {
+                    match selftest::compare_canvases(&cpu.canvas_data, &gpu.canvas_data) {
+                        Ok(()) => SelftestCheck::new(
+                            "gpu_execution",
+                            CheckStatus::Pass,
+                            format!("{} cycles, matches CPU canvas", gpu.cycles_executed),
+                        ),
+                        Err(diff) => SelftestCheck::new("gpu_execution", CheckStatus::Fail, diff),
+                    }
+                }
+            });
+        }
+
+        checks.push(
+            match self
+                .gvpie_analyzer
+                .analyze_component(selftest::SAMPLE_COMPONENT)
+                .await
+            {
+                Ok(report) => SelftestCheck::new(
+                    "analyzer",
+                    CheckStatus::Pass,
+                    format!(
+                        "{}: {} suggestions",
+                        selftest::SAMPLE_COMPONENT,
+                        report.optimization_suggestions.len()
+                    ),
+                ),
+                Err(e) => SelftestCheck::new("analyzer", CheckStatus::Fail, e.to_string()),
+            },
+        );
+
+        selftest::SelftestReport::new(self.pixel_backends(), self.gpu_memory_report(), checks)
+    }
+
     pub async fn list_cartridges(&self) -> Vec<Cartridge> {
         let manager = self.cartridge_manager.read().await;
         manager.list()
     }
 
+    pub async fn search_cartridges(&self, query: &str) -> Vec<Cartridge> {
+        let manager = self.cartridge_manager.read().await;
+        manager.search(query)
+    }
+
     pub async fn get_cartridge(&self, id: &str) -> Option<Cartridge> {
         let manager = self.cartridge_manager.read().await;
         manager.get(id)
@@ -116,10 +339,70 @@ impl AiRuntime {
         input_data: Option<&str>,
     ) -> Result<ExecutionResult> {
         let start = std::time::Instant::now();
-        let manager = self.cartridge_manager.read().await;
 
-        // Execute the cartridge
-        let output_data = manager.execute(cartridge_id, input_data)?;
+        // Execute the cartridge. The read lock is dropped before the GPU and
+        // database awaits below so writers aren't blocked for their duration.
+        let (output_data, cartridge, cache_key, generation) = {
+            let manager = self.cartridge_manager.read().await;
+            let output_data = manager.execute(cartridge_id, input_data)?;
+            let cartridge = manager
+                .get(cartridge_id)
+                .ok_or_else(|| cartridges::CartridgeError::NotFound(cartridge_id.to_string()))?;
+            let cache_key = ExecutionKey::new(&cartridge, input_data);
+            // Read under the cartridge lock so a concurrent update's invalidation is ordered after it
+            let generation = self.execution_cache().generation(cartridge_id);
+            (output_data, cartridge, cache_key, generation)
+        };
+
+        let cached = self.execution_cache().get(&cache_key);
+        let result = match cached {
+            Some(cached) => ExecutionResult {
+                duration_ms: start.elapsed().as_millis() as u64,
+                from_cache: true,
+                ..cached
+            },
+            None => {
+                let result = self.run_cartridge(&cartridge, output_data, start).await?;
+                self.execution_cache()
+                    .insert(cache_key, result.clone(), generation);
+                result
+            }
+        };
+
+        // History is best-effort: the cartridge already ran, so a write failure shouldn't fail it
+        if let Err(err) = self
+            .database
+            .record_cartridge_execution(
+                &CartridgeExecutionRecord {
+                    cartridge_id: cartridge_id.to_string(),
+                    input: input_data.map(str::to_string),
+                    executed_at: chrono::Utc::now(),
+                    result_hash: models::hash_bytes(&result.data),
+                },
+                MAX_EXECUTION_HISTORY,
+            )
+            .await
+        {
+            tracing::warn!(cartridge_id, error = %err, "Failed to record cartridge execution");
+        }
+
+        Ok(result)
+    }
+
+    fn execution_cache(&self) -> std::sync::MutexGuard<'_, ExecutionCache<ExecutionResult>> {
+        self.execution_cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Assemble and (if possible) glyph-expand a cartridge's output
+    async fn run_cartridge(
+        &self,
+        cartridge: &Cartridge,
+        output_data: Vec<u8>,
+        start: std::time::Instant,
+    ) -> Result<ExecutionResult> {
+        let program = self.assemble_cartridge(cartridge)?;
 
         // GPU GLYPH EXPANSION INTEGRATION
         let (backend, glyphs_expanded) = if self.gpu_available() {
@@ -131,19 +414,74 @@ impl AiRuntime {
             ("cpu".to_string(), false)
         };
 
-        let result = ExecutionResult {
+        Ok(ExecutionResult {
             output: format!(
                 "Executed cartridge: {} ({} bytes)",
-                cartridge_id,
+                cartridge.id,
                 output_data.len()
             ),
             backend,
             duration_ms: start.elapsed().as_millis() as u64,
             data: output_data,
             glyphs_expanded, // NEW: Report if glyph expansion occurred
-        };
+            instructions: program.map_or(0, |program| program.len()),
+            from_cache: false,
+        })
+    }
 
-        Ok(result)
+    /// Recorded executions of a cartridge, oldest first
+    pub async fn cartridge_history(
+        &self,
+        cartridge_id: &str,
+    ) -> Result<Vec<CartridgeExecutionRecord>> {
+        self.database.cartridge_history(cartridge_id).await
+    }
+
+    /// Resource averages and activity counts over the last `hours` hours
+    pub async fn stats(&self, hours: i64) -> Result<RuntimeStats> {
+        if !(1..=MAX_STATS_HOURS).contains(&hours) {
+            return Err(AiRuntimeError::validation(format!(
+                "hours must be between 1 and {}",
+                MAX_STATS_HOURS
+            )));
+        }
+        let since = chrono::TimeDelta::try_hours(hours)
+            .and_then(|window| chrono::Utc::now().checked_sub_signed(window))
+            .ok_or_else(|| AiRuntimeError::validation("hours window is out of range"))?;
+        let patterns = self.database.analyze_patterns_since(since).await?;
+        let executions_by_cartridge = self
+            .database
+            .cartridge_execution_counts_since(since)
+            .await?;
+
+        Ok(RuntimeStats {
+            window_hours: hours,
+            since,
+            resources: patterns.resource_trends,
+            decisions_by_action: self.database.decision_counts_since(since).await?,
+            events_by_kind: self.database.event_counts_since(since).await?,
+            cartridge_executions: executions_by_cartridge.values().sum(),
+            executions_by_cartridge,
+        })
+    }
+
+    /// Re-run the `index`th recorded execution of a cartridge with its original input
+    pub async fn replay_cartridge(
+        &self,
+        cartridge_id: &str,
+        index: usize,
+    ) -> Result<(CartridgeExecutionRecord, ExecutionResult)> {
+        let history = self.cartridge_history(cartridge_id).await?;
+        let record = history.into_iter().nth(index).ok_or_else(|| {
+            AiRuntimeError::not_found(format!(
+                "No execution #{} recorded for cartridge {}",
+                index, cartridge_id
+            ))
+        })?;
+        let result = self
+            .execute_cartridge(cartridge_id, record.input.as_deref())
+            .await?;
+        Ok((record, result))
     }
 
     #[cfg(feature = "gpu")]
@@ -175,31 +513,175 @@ impl AiRuntime {
     }
 
     pub async fn create_cartridge(&self, cartridge: Cartridge) -> Result<Cartridge> {
-        let mut manager = self.cartridge_manager.write().await;
-        manager.create_cartridge(cartridge.clone())?;
+        let created = cartridge.clone();
+        self.write_cartridges(move |manager| manager.create_cartridge(created))
+            .await?;
         Ok(cartridge)
     }
 
     pub async fn update_cartridge(&self, cartridge: Cartridge) -> Result<Cartridge> {
-        let mut manager = self.cartridge_manager.write().await;
-        manager.update_cartridge(cartridge.clone())?;
+        let updated = cartridge.clone();
+        let _manager = self
+            .write_cartridges(move |manager| manager.update_cartridge(updated))
+            .await?;
+        self.execution_cache().invalidate(&cartridge.id);
         Ok(cartridge)
     }
 
     pub async fn delete_cartridge(&self, id: &str) -> Result<()> {
-        let mut manager = self.cartridge_manager.write().await;
-        manager.delete_cartridge(id)?;
+        let deleted = id.to_string();
+        let _manager = self
+            .write_cartridges(move |manager| manager.delete_cartridge(&deleted))
+            .await?;
+        self.execution_cache().invalidate(id);
         Ok(())
     }
 
+    /// Runs a cartridge write on the blocking pool, since it takes the cross-process index
+    /// lock and does file I/O. The write guard is handed back so callers can invalidate the
+    /// execution cache before readers see the change.
+    async fn write_cartridges(
+        &self,
+        write: impl FnOnce(
+                &mut cartridges::CartridgeManager,
+            ) -> std::result::Result<(), cartridges::CartridgeError>
+            + Send
+            + 'static,
+    ) -> Result<OwnedRwLockWriteGuard<cartridges::CartridgeManager>> {
+        let mut manager = self.cartridge_manager.clone().write_owned().await;
+        let (manager, written) = tokio::task::spawn_blocking(move || {
+            let written = write(&mut manager);
+            (manager, written)
+        })
+        .await
+        .map_err(|err| AiRuntimeError::internal(err.to_string()))?;
+        written?;
+        Ok(manager)
+    }
+
     pub async fn execute_pixel_program(
         &self,
         request: PixelProgramRequest,
     ) -> Result<PixelProgramResponse> {
-        self.pixel_vm
-            .execute_program(request)
+        self.execute_pixel_program_cancellable(request, tokio_util::sync::CancellationToken::new())
             .await
-            .map_err(AiRuntimeError::AnyhowError)
+    }
+
+    /// Run `program` on both backends, reporting the first cycle after which their canvases
+    /// differ. Cancelling `cancel` abandons the diff with an internal "cancelled" error.
+    pub async fn diff_backends(
+        &self,
+        program: Vec<PixelInstruction>,
+        canvas_width: u32,
+        canvas_height: u32,
+        max_cycles: u64,
+        cancel: tokio_util::sync::CancellationToken,
+    ) -> Result<BackendDiff> {
+        if !self.gpu_available() {
+            return Err(AiRuntimeError::gpu_unavailable(
+                "backend diff needs a GPU core to compare against",
+            ));
+        }
+        let run = |backend, max_cycles| {
+            let request = PixelProgramRequest {
+                program: program.clone(),
+                backend,
+                max_cycles,
+                canvas_width,
+                canvas_height,
+            };
+            let cancel = cancel.clone();
+            async move {
+                // Straight to the VM: replays shouldn't skew the opcode counts
+                let response = self
+                    .pixel_vm
+                    .execute_program_cancellable(request, cancel)
+                    .await?;
+                match response.error {
+                    Some(error) => Err(AiRuntimeError::internal(error)),
+                    None => Ok(response),
+                }
+            }
+        };
+
+        let cpu = run(ExecutionBackend::Cpu, max_cycles).await?;
+        let gpu = run(ExecutionBackend::Gpu, max_cycles).await?;
+        let mut diff = BackendDiff {
+            diverged: false,
+            first_divergent_step: None,
+            instruction_index: None,
+            differing_pixels: Vec::new(),
+            cpu_cycles: cpu.cycles_executed,
+            gpu_cycles: gpu.cycles_executed,
+        };
+        if cpu.canvas_data == gpu.canvas_data && cpu.cycles_executed == gpu.cycles_executed {
+            return Ok(diff);
+        }
+        diff.diverged = true;
+        if cpu.canvas_data == gpu.canvas_data {
+            // Same pixels at the end; only the cycle counts disagree
+            return Ok(diff);
+        }
+
+        // Once the canvases differ they keep differing, so binary search the cycle budget
+        let (mut low, mut high) = (1, cpu.cycles_executed.max(gpu.cycles_executed));
+        let mut differing = differing_pixels(&cpu.canvas_data, &gpu.canvas_data, canvas_width);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let cpu = run(ExecutionBackend::Cpu, mid).await?;
+            let gpu = run(ExecutionBackend::Gpu, mid).await?;
+            let pixels = differing_pixels(&cpu.canvas_data, &gpu.canvas_data, canvas_width);
+            if pixels.is_empty() && cpu.canvas_data.len() == gpu.canvas_data.len() {
+                low = mid + 1;
+            } else {
+                high = mid;
+                differing = pixels;
+            }
+        }
+        let instruction_index = if low > 1 {
+            run(ExecutionBackend::Cpu, low - 1)
+                .await?
+                .instruction_pointer
+        } else {
+            0
+        };
+        diff.first_divergent_step = Some(low);
+        diff.instruction_index = Some(instruction_index);
+        diff.differing_pixels = differing;
+        Ok(diff)
+    }
+
+    /// Like `execute_pixel_program`, but gives up with a "cancelled" response once `cancel` fires
+    pub async fn execute_pixel_program_cancellable(
+        &self,
+        request: PixelProgramRequest,
+        cancel: tokio_util::sync::CancellationToken,
+    ) -> Result<PixelProgramResponse> {
+        let program = request.program.clone();
+        let response = self
+            .pixel_vm
+            .execute_program_cancellable(request, cancel)
+            .await?;
+        if response.success {
+            self.opcode_counts
+                .record(&program, response.cycles_executed);
+        }
+        Ok(response)
+    }
+
+    /// Executed steps per opcode over recent pixel executions
+    pub fn opcode_counts(&self) -> pixel_vm::OpcodeCounts {
+        self.opcode_counts.snapshot()
+    }
+
+    /// Executed steps of every opcode the VM defines over recent pixel executions, zero for
+    /// those that haven't run
+    pub fn opcode_coverage(&self) -> std::collections::HashMap<gvpie_core::PixelOp, u64> {
+        let executed = self.opcode_counts().executed;
+        pixel_vm::PIXEL_OPS
+            .iter()
+            .map(|(op, _)| (*op, executed.get(op).copied().unwrap_or(0)))
+            .collect()
     }
 
     pub fn assemble_pixel_program(&self, source: &str) -> Result<Vec<PixelInstruction>> {
@@ -208,16 +690,86 @@ impl AiRuntime {
             .map_err(AiRuntimeError::AnyhowError)
     }
 
+    /// Assemble a cartridge according to its `code_format`; `None` for binary cartridges
+    pub fn assemble_cartridge(
+        &self,
+        cartridge: &Cartridge,
+    ) -> Result<Option<Vec<PixelInstruction>>> {
+        let program = match cartridge.code_format {
+            CodeFormat::PixelAsm => self.pixel_vm.assemble_from_text(&cartridge.code)?,
+            CodeFormat::RawPixels => self
+                .pixel_vm
+                .assemble_from_pixels(&cartridge.raw_pixels()?)?,
+            CodeFormat::Binary => return Ok(None),
+        };
+        Ok(Some(program))
+    }
+
+    /// Configuration loaded when the runtime was created
+    pub fn config(&self) -> &config::Config {
+        &self.config
+    }
+
+    /// Canvas and cycle defaults and maximums applied to API pixel requests
+    pub fn pixel_limits(&self) -> &config::PixelLimits {
+        &self.config.pixel
+    }
+
     pub fn pixel_backends(&self) -> Vec<String> {
         self.pixel_vm.available_backends()
     }
 
+    /// Version, GPU and feature summary logged once by `new`
+    pub fn startup_report(&self) -> StartupReport {
+        let mut features = Vec::new();
+        if cfg!(feature = "gpu") {
+            features.push("gpu".to_string());
+        }
+        if cfg!(feature = "otel") {
+            features.push("otel".to_string());
+        }
+
+        StartupReport {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            gpu: if self.gpu_available() { "wgpu" } else { "none" }.to_string(),
+            features,
+            db_path: self.database.path().to_path_buf(),
+            log_dir: self.log_dir.clone(),
+            pixel_backends: self.pixel_backends(),
+        }
+    }
+
     // GVPIe Analysis Methods
 
     /// Analyze the entire GVPIe codebase and provide comprehensive insights
     pub async fn analyze_gvpie_codebase(&self) -> Result<gvpie_analysis::GvpieAnalysisReport> {
-        let mut analyzer = self.gvpie_analyzer.write().await;
-        analyzer.analyze_gvpie_codebase().await
+        self.record_measurements();
+        self.gvpie_analyzer.analyze_gvpie_codebase().await
+    }
+
+    /// Full analysis that reports each suggestion and finding to `on_item` as it is produced
+    pub async fn analyze_gvpie_codebase_streaming<F>(
+        &self,
+        on_item: F,
+    ) -> Result<gvpie_analysis::GvpieAnalysisReport>
+    where
+        F: FnMut(gvpie_analysis::AnalysisItem) + Send,
+    {
+        self.record_measurements();
+        self.gvpie_analyzer
+            .analyze_gvpie_codebase_streaming(on_item)
+            .await
+    }
+
+    /// Full analysis that gives up after `deadline`, returning the items produced so far
+    pub async fn analyze_gvpie_codebase_within(
+        &self,
+        deadline: std::time::Duration,
+    ) -> Result<gvpie_analysis::BoundedAnalysis> {
+        gvpie_analysis::analyze_within(deadline, |on_item| {
+            self.analyze_gvpie_codebase_streaming(on_item)
+        })
+        .await
     }
 
     /// Analyze a specific GVPIe component
@@ -225,8 +777,7 @@ impl AiRuntime {
         &self,
         component_path: P,
     ) -> Result<gvpie_analysis::GvpieAnalysisReport> {
-        let mut analyzer = self.gvpie_analyzer.write().await;
-        analyzer.analyze_component(component_path).await
+        self.gvpie_analyzer.analyze_component(component_path).await
     }
 
     /// Get real-time development suggestions for changed files
@@ -234,8 +785,7 @@ impl AiRuntime {
         &self,
         changed_files: &[PathBuf],
     ) -> Result<Vec<gvpie_analysis::OptimizationSuggestion>> {
-        let analyzer = self.gvpie_analyzer.read().await;
-        analyzer
+        self.gvpie_analyzer
             .suggest_improvements_for_changes(changed_files)
             .await
     }
@@ -245,18 +795,16 @@ impl AiRuntime {
         &self,
         changes: &[String],
     ) -> Result<gvpie_analysis::PerformanceInsights> {
-        let analyzer = self.gvpie_analyzer.read().await;
-        analyzer.predict_performance_impact(changes).await
+        self.gvpie_analyzer
+            .predict_performance_impact(changes)
+            .await
     }
 
     /// Get AI-powered development assistance for GVPIe
     pub async fn get_gvpie_development_assistance(&self) -> Result<GvpieDevelopmentAssistance> {
-        let analyzer = self.gvpie_analyzer.read().await;
-
         // Analyze current state
-        let mut full_analyzer = self.gvpie_analyzer.write().await;
-        let analysis_report = full_analyzer.analyze_gvpie_codebase().await?;
-        drop(full_analyzer);
+        self.record_measurements();
+        let analysis_report = self.gvpie_analyzer.analyze_gvpie_codebase().await?;
 
         // Generate development recommendations
         let recommendations = self
@@ -269,10 +817,23 @@ impl AiRuntime {
             next_actions: self
                 .suggest_next_development_actions(&analysis_report)
                 .await?,
-            performance_predictions: analyzer.predict_performance_impact(&[]).await?,
+            performance_predictions: self.gvpie_analyzer.predict_performance_impact(&[]).await?,
         })
     }
 
+    /// Program data dispatched through the execution bridge and the device memory budget
+    pub fn gpu_memory_report(&self) -> gpu_bridge::GpuMemoryReport {
+        self.gpu_bridge.memory_report()
+    }
+
+    /// Hand dispatched program bytes and opcode counts to the analyzer before an analysis
+    fn record_measurements(&self) {
+        self.gvpie_analyzer
+            .record_program_memory(self.gpu_bridge.memory_report());
+        self.gvpie_analyzer
+            .record_opcode_counts(self.opcode_counts.snapshot());
+    }
+
     // Private helper methods for development assistance
     async fn generate_development_recommendations(
         &self,
@@ -382,6 +943,22 @@ pub struct ExecutionResult {
     pub duration_ms: u64,
     pub data: Vec<u8>,
     pub glyphs_expanded: bool, // NEW
+    /// Pixel instructions assembled from the cartridge code (0 for binary cartridges)
+    pub instructions: usize,
+    /// Served from the execution cache rather than recomputed
+    pub from_cache: bool,
+}
+
+/// Capabilities of a freshly constructed runtime, for log aggregators and bug reports
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StartupReport {
+    pub version: String,
+    /// GPU backend in use, or "none"
+    pub gpu: String,
+    pub features: Vec<String>,
+    pub db_path: PathBuf,
+    pub log_dir: PathBuf,
+    pub pixel_backends: Vec<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -411,6 +988,26 @@ pub struct NextAction {
     pub estimated_time: String,
 }
 
+fn auto_gpu_threshold() -> usize {
+    std::env::var("GVPIE_AUTO_GPU_THRESHOLD")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(pixel_vm::DEFAULT_AUTO_GPU_THRESHOLD)
+}
+
+fn max_gpu_concurrency() -> usize {
+    std::env::var("GVPIE_MAX_GPU_CONCURRENCY")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(gpu_bridge::DEFAULT_MAX_GPU_CONCURRENCY)
+}
+
+fn log_dir() -> PathBuf {
+    std::env::var("GVPIE_LOG_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("./logs"))
+}
+
 fn cartridge_storage_path() -> PathBuf {
     std::env::var("GVPIE_CARTRIDGE_PATH")
         .map(PathBuf::from)
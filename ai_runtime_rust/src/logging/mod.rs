@@ -3,9 +3,14 @@
 //! Provides ECS (Elastic Common Schema) and ASFF (AWS Security Finding Format) compliant logging.
 //! Based on Python's ai_runtime/core/structured_logger.py
 
+#[cfg(feature = "otel")]
+mod otlp;
 mod structured;
 
+#[cfg(feature = "otel")]
+pub use otlp::OtlpExporter;
+
 pub use structured::{
     EcsEvent, IncidentSeverity, LogSeverity, PerformanceMetrics, SecurityFinding, StructuredLogger,
-    SystemOperation,
+    SystemOperation, DEFAULT_REDACTED_KEYS,
 };
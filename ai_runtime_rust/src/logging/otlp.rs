@@ -0,0 +1,310 @@
+//! OTLP/HTTP (JSON) log exporter, enabled by the `otel` feature
+//!
+//! Events are queued to a background thread and POSTed to
+//! `$OTEL_EXPORTER_OTLP_ENDPOINT/v1/logs` so logging never blocks on the
+//! collector. The queue is bounded: while the collector is slow or down,
+//! records beyond `QUEUE_CAPACITY` are dropped and counted, and whatever has
+//! queued up is sent as one batch per request. Only plain `http://` endpoints
+//! are supported.
+
+use super::structured::{EcsEvent, LogSeverity};
+use crate::errors::{AiRuntimeError, Result};
+use serde_json::{json, Value as JsonValue};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::Duration;
+
+const ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const LOGS_PATH: &str = "/v1/logs";
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// Records held for the worker before new ones are dropped
+const QUEUE_CAPACITY: usize = 1024;
+/// Most records sent in one export request
+const MAX_BATCH: usize = 256;
+
+/// One OTLP log record and the service it belongs to
+#[derive(Debug)]
+struct QueuedRecord {
+    service: (String, String),
+    record: JsonValue,
+}
+
+/// Ships log records to an OTLP collector
+#[derive(Debug)]
+pub struct OtlpExporter {
+    endpoint: String,
+    sender: SyncSender<QueuedRecord>,
+    dropped: AtomicU64,
+}
+
+impl OtlpExporter {
+    /// Exporter for `OTEL_EXPORTER_OTLP_ENDPOINT`, if it is set
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var(ENDPOINT_ENV).ok()?;
+        match Self::new(&endpoint) {
+            Ok(exporter) => Some(exporter),
+            Err(e) => {
+                tracing::warn!("OTLP export disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    pub fn new(endpoint: &str) -> Result<Self> {
+        let (host, base_path) = parse_endpoint(endpoint)?;
+        let path = format!("{}{}", base_path.trim_end_matches('/'), LOGS_PATH);
+        let (sender, receiver) = mpsc::sync_channel::<QueuedRecord>(QUEUE_CAPACITY);
+
+        std::thread::Builder::new()
+            .name("otlp-exporter".to_string())
+            .spawn(move || {
+                while let Ok(first) = receiver.recv() {
+                    let mut batch = vec![first];
+                    batch.extend(receiver.try_iter().take(MAX_BATCH - 1));
+                    let body = to_export_request(&batch).to_string();
+                    if let Err(e) = post_json(&host, &path, &body) {
+                        tracing::warn!(
+                            "OTLP export of {} records to {}{} failed: {}",
+                            batch.len(),
+                            host,
+                            path,
+                            e
+                        );
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            endpoint: endpoint.to_string(),
+            sender,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Records dropped because the queue was full
+    pub fn dropped_records(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queue one event as an OTLP log record, dropping it if the queue is full
+    pub fn export(&self, severity: LogSeverity, event: &EcsEvent) {
+        match self.sender.try_send(to_log_record(severity, event)) {
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            // The worker only exits when the exporter is dropped
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+/// OTLP severity number for a syslog severity
+fn severity_number(severity: LogSeverity) -> u8 {
+    match severity {
+        LogSeverity::Debug => 5,
+        LogSeverity::Info => 9,
+        LogSeverity::Notice => 10,
+        LogSeverity::Warning => 13,
+        LogSeverity::Error => 17,
+        LogSeverity::Critical => 18,
+        LogSeverity::Alert => 21,
+        LogSeverity::Emergency => 24,
+    }
+}
+
+fn string_attribute(key: &str, value: impl Into<String>) -> JsonValue {
+    json!({ "key": key, "value": { "stringValue": value.into() } })
+}
+
+/// Convert an ECS event to an OTLP `LogRecord`
+fn to_log_record(severity: LogSeverity, event: &EcsEvent) -> QueuedRecord {
+    let mut attributes = Vec::new();
+    if let Some(info) = &event.event {
+        if let Some(dataset) = &info.dataset {
+            attributes.push(string_attribute("event.dataset", dataset.as_str()));
+        }
+        if let Some(category) = &info.category {
+            attributes.push(string_attribute("event.category", category.join(",")));
+        }
+        if let Some(action) = &info.action {
+            attributes.push(string_attribute("event.action", action.as_str()));
+        }
+        if let Some(outcome) = &info.outcome {
+            attributes.push(string_attribute("event.outcome", outcome.as_str()));
+        }
+    }
+    let mut extra: Vec<_> = event.extra.iter().collect();
+    extra.sort_by(|a, b| a.0.cmp(b.0));
+    for (key, value) in extra {
+        attributes.push(string_attribute(key, value.to_string()));
+    }
+
+    let time_unix_nano = event
+        .timestamp
+        .timestamp_nanos_opt()
+        .unwrap_or_default()
+        .to_string();
+
+    QueuedRecord {
+        service: (event.service.name.clone(), event.service.version.clone()),
+        record: json!({
+            "timeUnixNano": time_unix_nano,
+            "severityNumber": severity_number(severity),
+            "severityText": severity.as_str().to_uppercase(),
+            "body": { "stringValue": event.message },
+            "attributes": attributes,
+        }),
+    }
+}
+
+/// Wrap a batch of records in an OTLP `ExportLogsServiceRequest`, one resource per service
+fn to_export_request(batch: &[QueuedRecord]) -> JsonValue {
+    let mut services: Vec<(&(String, String), Vec<&JsonValue>)> = Vec::new();
+    for queued in batch {
+        match services
+            .iter_mut()
+            .find(|(service, _)| **service == queued.service)
+        {
+            Some((_, records)) => records.push(&queued.record),
+            None => services.push((&queued.service, vec![&queued.record])),
+        }
+    }
+
+    let resource_logs: Vec<JsonValue> = services
+        .into_iter()
+        .map(|((name, version), records)| {
+            json!({
+                "resource": {
+                    "attributes": [
+                        string_attribute("service.name", name.as_str()),
+                        string_attribute("service.version", version.as_str()),
+                    ]
+                },
+                "scopeLogs": [{
+                    "scope": { "name": "ai_runtime" },
+                    "logRecords": records,
+                }]
+            })
+        })
+        .collect();
+    json!({ "resourceLogs": resource_logs })
+}
+
+/// Split `http://host:port/base` into `("host:port", "/base")`
+fn parse_endpoint(endpoint: &str) -> Result<(String, String)> {
+    let rest = endpoint.strip_prefix("http://").ok_or_else(|| {
+        AiRuntimeError::config(format!(
+            "unsupported OTLP endpoint {:?}: only http:// is supported",
+            endpoint
+        ))
+    })?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, ""),
+    };
+    if authority.is_empty() {
+        return Err(AiRuntimeError::config(format!(
+            "OTLP endpoint {:?} has no host",
+            endpoint
+        )));
+    }
+    let host = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    Ok((host, path.to_string()))
+}
+
+fn post_json(host: &str, path: &str, body: &str) -> std::io::Result<()> {
+    let mut stream = TcpStream::connect(host)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        host,
+        body.len(),
+        body
+    )?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(std::io::Error::other(format!(
+            "collector responded {:?}",
+            response.lines().next().unwrap_or_default()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_endpoints() {
+        assert_eq!(
+            parse_endpoint("http://collector:4318").unwrap(),
+            ("collector:4318".to_string(), String::new())
+        );
+        assert_eq!(
+            parse_endpoint("http://collector/otlp/").unwrap(),
+            ("collector:80".to_string(), "/otlp/".to_string())
+        );
+        assert!(parse_endpoint("https://collector:4318").is_err());
+    }
+
+    #[test]
+    fn full_queue_drops_records_and_batches_the_rest() {
+        use super::super::structured::{LogInfo, ServiceInfo};
+
+        let (sender, receiver) = mpsc::sync_channel(2);
+        let exporter = OtlpExporter {
+            endpoint: "http://collector:4318".to_string(),
+            sender,
+            dropped: AtomicU64::new(0),
+        };
+        for message in ["first", "second", "third"] {
+            let event = EcsEvent {
+                timestamp: chrono::Utc::now(),
+                log: LogInfo {
+                    level: "info".to_string(),
+                },
+                message: message.to_string(),
+                service: ServiceInfo {
+                    name: "ai_runtime".to_string(),
+                    version: "test".to_string(),
+                    service_type: "runtime".to_string(),
+                },
+                event: None,
+                process: None,
+                extra: Default::default(),
+            };
+            exporter.export(LogSeverity::Info, &event);
+        }
+        assert_eq!(exporter.dropped_records(), 1);
+
+        let batch: Vec<_> = receiver.try_iter().collect();
+        let request = to_export_request(&batch);
+        let resource_logs = request["resourceLogs"].as_array().unwrap();
+        assert_eq!(resource_logs.len(), 1);
+        let records = resource_logs[0]["scopeLogs"][0]["logRecords"]
+            .as_array()
+            .unwrap();
+        let messages: Vec<_> = records
+            .iter()
+            .map(|record| record["body"]["stringValue"].as_str().unwrap())
+            .collect();
+        assert_eq!(messages, ["first", "second"]);
+    }
+}
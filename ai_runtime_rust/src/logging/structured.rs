@@ -190,11 +190,20 @@ pub struct SystemOperation {
     pub user: Option<String>,
 }
 
+/// Key fragments (case-insensitive) whose values are redacted by default
+pub const DEFAULT_REDACTED_KEYS: &[&str] = &["token", "secret", "key", "password"];
+
+/// Replacement for redacted values
+const REDACTED: &str = "***";
+
 /// High-assurance structured logger with ECS/ASFF compliance
 pub struct StructuredLogger {
     log_dir: PathBuf,
     service_name: String,
     service_version: String,
+    redacted_keys: Vec<String>,
+    #[cfg(feature = "otel")]
+    otlp: Option<super::OtlpExporter>,
 }
 
 impl StructuredLogger {
@@ -206,9 +215,35 @@ impl StructuredLogger {
             log_dir,
             service_name: "ai_runtime".to_string(),
             service_version: "0.1.0".to_string(),
+            redacted_keys: DEFAULT_REDACTED_KEYS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            #[cfg(feature = "otel")]
+            otlp: super::OtlpExporter::from_env(),
         })
     }
 
+    /// Export performance and audit events through `exporter` in addition to the log files
+    #[cfg(feature = "otel")]
+    pub fn with_otlp_exporter(mut self, exporter: super::OtlpExporter) -> Self {
+        self.otlp = Some(exporter);
+        self
+    }
+
+    /// Replace the key fragments whose values are redacted from event context
+    pub fn with_redacted_keys<I, S>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.redacted_keys = keys
+            .into_iter()
+            .map(|key| key.into().to_lowercase())
+            .collect();
+        self
+    }
+
     /// Map log severity to incident severity tier
     pub fn get_incident_severity(&self, severity: LogSeverity) -> IncidentSeverity {
         match severity {
@@ -237,6 +272,7 @@ impl StructuredLogger {
         if let Some(ctx) = &context {
             ecs_event.extra.extend(ctx.clone());
         }
+        self.redact(&mut ecs_event.extra);
 
         // Create ASFF finding
         let asff_finding = SecurityFinding {
@@ -312,8 +348,10 @@ impl StructuredLogger {
         event
             .extra
             .insert("metrics".to_string(), serde_json::to_value(&metrics)?);
+        self.redact(&mut event.extra);
 
         self.write_log("performance.log", &event)?;
+        self.export(severity, &event);
 
         Ok(())
     }
@@ -346,10 +384,45 @@ impl StructuredLogger {
         }
 
         self.write_log("audit.log", &event)?;
+        self.export(severity, &event);
 
         Ok(())
     }
 
+    fn is_redacted(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.redacted_keys
+            .iter()
+            .any(|fragment| key.contains(fragment.as_str()))
+    }
+
+    /// Mask sensitive values in caller-supplied fields, including nested objects
+    fn redact(&self, fields: &mut HashMap<String, JsonValue>) {
+        for (key, value) in fields.iter_mut() {
+            if self.is_redacted(key) {
+                *value = JsonValue::String(REDACTED.to_string());
+            } else {
+                self.redact_value(value);
+            }
+        }
+    }
+
+    fn redact_value(&self, value: &mut JsonValue) {
+        match value {
+            JsonValue::Object(map) => {
+                for (key, nested) in map.iter_mut() {
+                    if self.is_redacted(key) {
+                        *nested = JsonValue::String(REDACTED.to_string());
+                    } else {
+                        self.redact_value(nested);
+                    }
+                }
+            }
+            JsonValue::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+
     /// Create base ECS event
     fn create_base_event(
         &self,
@@ -376,6 +449,16 @@ impl StructuredLogger {
         }
     }
 
+    #[cfg(feature = "otel")]
+    fn export(&self, severity: LogSeverity, event: &EcsEvent) {
+        if let Some(exporter) = &self.otlp {
+            exporter.export(severity, event);
+        }
+    }
+
+    #[cfg(not(feature = "otel"))]
+    fn export(&self, _severity: LogSeverity, _event: &EcsEvent) {}
+
     /// Write log entry to file
     fn write_log(&self, filename: &str, event: &EcsEvent) -> Result<()> {
         let log_path = self.log_dir.join(filename);
@@ -455,6 +538,51 @@ mod tests {
         assert!(security_log.exists());
     }
 
+    #[test]
+    fn test_security_context_is_redacted() {
+        let dir = tempdir().unwrap();
+        let logger = StructuredLogger::new(dir.path()).unwrap();
+
+        let mut context = HashMap::new();
+        context.insert("capability_token".to_string(), json!("cap-abc123"));
+        context.insert(
+            "request".to_string(),
+            json!({"headers": [{"API_KEY": "k-999"}], "path": "/api/run"}),
+        );
+        logger
+            .log_security_event(
+                LogSeverity::Warning,
+                "Capability presented".to_string(),
+                "SEC-002".to_string(),
+                3.0,
+                Some(context),
+            )
+            .unwrap();
+
+        let written = std::fs::read_to_string(dir.path().join("structured_daemon.log")).unwrap();
+        assert!(!written.contains("cap-abc123"));
+        assert!(!written.contains("k-999"));
+        let event: JsonValue = serde_json::from_str(written.trim()).unwrap();
+        assert_eq!(event["capability_token"], "***");
+        assert_eq!(event["request"]["headers"][0]["API_KEY"], "***");
+        assert_eq!(event["request"]["path"], "/api/run");
+    }
+
+    #[test]
+    fn test_redaction_list_is_configurable() {
+        let dir = tempdir().unwrap();
+        let logger = StructuredLogger::new(dir.path())
+            .unwrap()
+            .with_redacted_keys(["Session"]);
+
+        let mut fields = HashMap::new();
+        fields.insert("session_id".to_string(), json!("s-1"));
+        fields.insert("api_key".to_string(), json!("kept"));
+        logger.redact(&mut fields);
+        assert_eq!(fields["session_id"], "***");
+        assert_eq!(fields["api_key"], "kept");
+    }
+
     #[test]
     fn test_performance_logging() {
         let dir = tempdir().unwrap();
@@ -478,4 +606,62 @@ mod tests {
         let perf_log = dir.path().join("performance.log");
         assert!(perf_log.exists());
     }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_performance_event_is_exported_over_otlp() {
+        use std::io::{BufRead, BufReader, Read};
+        use std::net::TcpListener;
+
+        let collector = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", collector.local_addr().unwrap());
+
+        let dir = tempdir().unwrap();
+        let logger = StructuredLogger::new(dir.path())
+            .unwrap()
+            .with_otlp_exporter(super::super::OtlpExporter::new(&endpoint).unwrap());
+        let metrics = PerformanceMetrics {
+            duration_ms: 42,
+            cpu_percent: None,
+            memory_mb: None,
+            custom: HashMap::new(),
+        };
+        logger
+            .log_performance_event(LogSeverity::Warning, "Slow frame".to_string(), metrics)
+            .unwrap();
+
+        let (stream, _) = collector.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        assert!(request_line.starts_with("POST /v1/logs "), "{request_line}");
+
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some(len) = header.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = len.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+
+        let body: JsonValue = serde_json::from_slice(&body).unwrap();
+        let record = &body["resourceLogs"][0]["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(record["body"]["stringValue"], "Slow frame");
+        assert_eq!(record["severityNumber"], 13);
+        assert!(record["attributes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|attr| attr["value"]["stringValue"] == "ai_runtime.performance"));
+    }
 }
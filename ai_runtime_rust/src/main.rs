@@ -4,8 +4,6 @@ use ai_runtime::{AiRuntime, ApiServer};
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt().with_target(false).init();
 
-    tracing::info!("Starting AI Runtime");
-
     let runtime = AiRuntime::new()
         .await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error>)?;
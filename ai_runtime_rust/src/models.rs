@@ -21,8 +21,12 @@ pub struct Cartridge {
 }
 
 pub fn hash_program(program: &str) -> String {
+    hash_bytes(program.as_bytes())
+}
+
+pub fn hash_bytes(data: &[u8]) -> String {
     use sha2::{Digest, Sha256};
     let mut hasher = Sha256::new();
-    hasher.update(program.as_bytes());
+    hasher.update(data);
     format!("{:x}", hasher.finalize())
 }
@@ -3,6 +3,7 @@
 //! Provides real-time system metrics including CPU, memory, disk, and network usage.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 use sysinfo::{Disks, Networks, System};
 
@@ -16,6 +17,21 @@ pub struct SystemMetrics {
     pub network_rx_bytes: u64,
     pub network_tx_bytes: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Optional metrics enabled through `MonitorConfig::metrics`, keyed by name
+    #[serde(default)]
+    pub extra: HashMap<String, f64>,
+}
+
+/// Optional metrics a deployment can opt into on top of cpu/memory/disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackedMetric {
+    /// Per-interface receive/transmit bytes since the last sample
+    Network,
+    /// VRAM in use, where the driver exposes it through sysfs
+    GpuMemory,
+    /// File descriptors held open by this process
+    OpenFileDescriptors,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,17 +47,33 @@ pub struct SystemMonitor {
     system: System,
     networks: Networks,
     disks: Disks,
+    tracked: Vec<TrackedMetric>,
 }
 
 impl SystemMonitor {
     pub fn new() -> Self {
+        Self::with_metrics(Vec::new())
+    }
+
+    /// Monitor that also samples the given optional metrics into `SystemMetrics.extra`
+    pub fn with_metrics(tracked: Vec<TrackedMetric>) -> Self {
         Self {
             system: System::new_all(),
             networks: Networks::new_with_refreshed_list(),
             disks: Disks::new_with_refreshed_list(),
+            tracked,
         }
     }
 
+    /// Monitor sampling the metrics enabled in `config.metrics`
+    pub fn from_config(config: &crate::config::MonitorConfig) -> Self {
+        Self::with_metrics(config.metrics.clone())
+    }
+
+    pub fn tracked_metrics(&self) -> &[TrackedMetric] {
+        &self.tracked
+    }
+
     pub fn capture_system_state(&mut self) -> SystemMetrics {
         // Refresh all system data
         self.system.refresh_all();
@@ -94,6 +126,39 @@ impl SystemMonitor {
             network_tx_bytes += data.transmitted();
         }
 
+        let mut extra = HashMap::new();
+        for metric in &self.tracked {
+            match metric {
+                TrackedMetric::Network => {
+                    extra.insert("network_rx_bytes".to_string(), network_rx_bytes as f64);
+                    extra.insert("network_tx_bytes".to_string(), network_tx_bytes as f64);
+                    for (interface_name, data) in self.networks.iter() {
+                        extra.insert(
+                            format!("network.{}.rx_bytes", interface_name),
+                            data.received() as f64,
+                        );
+                        extra.insert(
+                            format!("network.{}.tx_bytes", interface_name),
+                            data.transmitted() as f64,
+                        );
+                    }
+                }
+                TrackedMetric::GpuMemory => {
+                    if let Some(used) = gpu_memory_used_bytes() {
+                        extra.insert(
+                            "gpu_memory_used_mb".to_string(),
+                            used as f64 / 1024.0 / 1024.0,
+                        );
+                    }
+                }
+                TrackedMetric::OpenFileDescriptors => {
+                    if let Some(count) = open_file_descriptors() {
+                        extra.insert("open_file_descriptors".to_string(), count as f64);
+                    }
+                }
+            }
+        }
+
         SystemMetrics {
             cpu_usage,
             memory_used_mb,
@@ -103,6 +168,7 @@ impl SystemMonitor {
             network_rx_bytes,
             network_tx_bytes,
             timestamp: chrono::Utc::now(),
+            extra,
         }
     }
 
@@ -121,6 +187,37 @@ impl SystemMonitor {
     }
 }
 
+/// Sum of VRAM in use across DRM cards that report it (amdgpu and friends)
+fn gpu_memory_used_bytes() -> Option<u64> {
+    sum_drm_counter("mem_info_vram_used")
+}
+
+/// Total VRAM across DRM cards that report it
+pub(crate) fn gpu_memory_total_bytes() -> Option<u64> {
+    sum_drm_counter("mem_info_vram_total")
+}
+
+fn sum_drm_counter(name: &str) -> Option<u64> {
+    let cards = std::fs::read_dir("/sys/class/drm").ok()?;
+    let mut total = None;
+    for card in cards.flatten() {
+        let path = card.path().join("device").join(name);
+        if let Ok(raw) = std::fs::read_to_string(path) {
+            if let Ok(used) = raw.trim().parse::<u64>() {
+                *total.get_or_insert(0) += used;
+            }
+        }
+    }
+    total
+}
+
+/// Open descriptors of this process, where `/proc` is available
+fn open_file_descriptors() -> Option<usize> {
+    std::fs::read_dir("/proc/self/fd")
+        .ok()
+        .map(|entries| entries.count())
+}
+
 impl Default for SystemMonitor {
     fn default() -> Self {
         Self::new()
@@ -145,5 +242,21 @@ mod tests {
         assert!(metrics.cpu_usage >= 0.0);
         assert!(metrics.memory_total_mb > 0);
         assert!(metrics.memory_usage_percent >= 0.0 && metrics.memory_usage_percent <= 100.0);
+        assert!(metrics.extra.is_empty());
+    }
+
+    #[test]
+    fn test_tracked_network_metric_is_sampled() {
+        let config: crate::config::MonitorConfig =
+            serde_yaml::from_str("metrics: [network]").unwrap();
+        let mut monitor = SystemMonitor::from_config(&config);
+        let metrics = monitor.capture_system_state();
+
+        assert_eq!(
+            metrics.extra.get("network_rx_bytes"),
+            Some(&(metrics.network_rx_bytes as f64))
+        );
+        assert!(metrics.extra.contains_key("network_tx_bytes"));
+        assert!(!metrics.extra.contains_key("open_file_descriptors"));
     }
 }
@@ -1,15 +1,294 @@
+use std::collections::{HashMap, VecDeque};
 use std::time::Instant;
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
 
+use crate::gpu_bridge::{GpuDispatchLimiter, ProgramTracker};
+use crate::AiRuntimeError;
 use anyhow::{anyhow, Result};
 use gvpie_core::{
     GpuMachineExecutor, PixelAssembler, PixelBackend, PixelExecutionOutcome, PixelExecutor,
-    PixelInstruction,
+    PixelInstruction, PixelOp,
 };
 use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+
+/// Error message of a response whose execution was cancelled before it finished
+pub const CANCELLED: &str = "cancelled";
+
+/// Response header carrying `canvas_fingerprint` of the returned canvas, as 16 hex digits.
+pub const CANVAS_FINGERPRINT_HEADER: &str = "x-canvas-fingerprint";
+
+/// Response header carrying the clamped `CanvasRegion` a partial canvas covers, as `x,y,w,h`.
+pub const CANVAS_REGION_HEADER: &str = "x-canvas-region";
+
+/// Sub-rectangle of a canvas, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanvasRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CanvasRegion {
+    /// Shrink the region to the part lying inside a `canvas_width` x `canvas_height` canvas
+    pub fn clamp(self, canvas_width: u32, canvas_height: u32) -> Self {
+        let x = self.x.min(canvas_width);
+        let y = self.y.min(canvas_height);
+        Self {
+            x,
+            y,
+            width: self.width.min(canvas_width - x),
+            height: self.height.min(canvas_height - y),
+        }
+    }
+}
+
+impl fmt::Display for CanvasRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl std::str::FromStr for CanvasRegion {
+    type Err = String;
+
+    /// Parses `x,y,w,h`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let fields = s
+            .split(',')
+            .map(|field| field.trim().parse::<u32>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid region {:?}: {}", s, e))?;
+        match fields[..] {
+            [x, y, width, height] => Ok(Self {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => Err(format!("invalid region {:?}: expected x,y,w,h", s)),
+        }
+    }
+}
+
+/// RGBA bytes of `region` within a `canvas_width` x `canvas_height` canvas, row by row.
+/// The region is clamped to the canvas first. Empty if the canvas is shorter than
+/// `canvas_width * canvas_height`, as with a failed run's empty `canvas_data`.
+pub fn canvas_region(
+    canvas: &[u8],
+    canvas_width: u32,
+    canvas_height: u32,
+    region: CanvasRegion,
+) -> Vec<u8> {
+    if canvas.len() < canvas_width as usize * canvas_height as usize * 4 {
+        return Vec::new();
+    }
+    let region = region.clamp(canvas_width, canvas_height);
+    let row_bytes = region.width as usize * 4;
+    let mut data = Vec::with_capacity(row_bytes * region.height as usize);
+    for row in region.y..region.y + region.height {
+        let start = (row as usize * canvas_width as usize + region.x as usize) * 4;
+        data.extend_from_slice(&canvas[start..start + row_bytes]);
+    }
+    data
+}
+
+/// Stable 64-bit FNV-1a hash of a canvas buffer, for compact comparisons in tests and clients.
+pub fn canvas_fingerprint(canvas: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    canvas.iter().fold(FNV_OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Glyphs from emptiest to densest, indexed by luminance
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Downsample an RGBA canvas to luminance-mapped ASCII art `cols` characters wide, one line per
+/// text row. Each row covers twice the height of a column, to suit terminal glyph proportions;
+/// fully transparent cells are spaces. Empty if the canvas is shorter than `width * height`.
+pub fn canvas_to_ascii(rgba: &[u8], width: u32, height: u32, cols: u32) -> String {
+    let (width, height) = (width as usize, height as usize);
+    if width == 0 || height == 0 || cols == 0 || rgba.len() < width * height * 4 {
+        return String::new();
+    }
+    let cols = (cols as usize).min(width);
+    let rows = (height * cols / (width * 2)).max(1);
+
+    let mut art = String::with_capacity((cols + 1) * rows);
+    for row in 0..rows {
+        let (y0, y1) = (row * height / rows, (row + 1) * height / rows);
+        for col in 0..cols {
+            let (x0, x1) = (col * width / cols, (col + 1) * width / cols);
+            let (mut luminance, mut opaque, mut pixels) = (0.0f32, 0, 0);
+            for y in y0..y1 {
+                for pixel in rgba[(y * width + x0) * 4..(y * width + x1) * 4].chunks_exact(4) {
+                    pixels += 1;
+                    if pixel[3] > 0 {
+                        opaque += 1;
+                        let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]].map(f32::from);
+                        luminance += (0.2126 * r + 0.7152 * g + 0.0722 * b) * a / 255.0;
+                    }
+                }
+            }
+            if opaque == 0 {
+                art.push(' ');
+                continue;
+            }
+            let level = luminance / pixels as f32 / 255.0;
+            let index = (level * (ASCII_RAMP.len() - 1) as f32).round() as usize;
+            art.push(ASCII_RAMP[index.min(ASCII_RAMP.len() - 1)] as char);
+        }
+        art.push('\n');
+    }
+    art
+}
+
+/// Coordinates of every pixel that differs between two RGBA canvases of the same `width`.
+pub fn differing_pixels(a: &[u8], b: &[u8], width: u32) -> Vec<(u32, u32)> {
+    let width = width.max(1) as usize;
+    a.chunks_exact(4)
+        .zip(b.chunks_exact(4))
+        .enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(index, _)| ((index % width) as u32, (index / width) as u32))
+        .collect()
+}
+
+/// Where the CPU and GPU backends first disagree on a program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendDiff {
+    pub diverged: bool,
+    /// 1-based cycle after which the canvases first differ
+    pub first_divergent_step: Option<u64>,
+    /// Index of the instruction executed on that cycle, per the CPU backend
+    pub instruction_index: Option<u32>,
+    pub differing_pixels: Vec<(u32, u32)>,
+    pub cpu_cycles: u64,
+    pub gpu_cycles: u64,
+}
+
+/// Every opcode the Pixel VM defines, with the mnemonic analyses report it under. `SET`, which
+/// writes a single pixel, is reported as `PUTPIX`.
+pub const PIXEL_OPS: [(PixelOp, &str); 7] = [
+    (PixelOp::NOP, "NOP"),
+    (PixelOp::SET, "PUTPIX"),
+    (PixelOp::FILL, "FILL"),
+    (PixelOp::LOAD, "LOAD"),
+    (PixelOp::STORE, "STORE"),
+    (PixelOp::JUMP, "JUMP"),
+    (PixelOp::HALT, "HALT"),
+];
+
+/// The opcode an instruction's `r` channel encodes, if it is one the VM defines
+pub fn pixel_op(opcode: u8) -> Option<PixelOp> {
+    PIXEL_OPS
+        .iter()
+        .find(|(op, _)| *op as u8 == opcode)
+        .map(|(op, _)| *op)
+}
+
+/// Mnemonic of `op`, as listed in `PIXEL_OPS`
+pub fn op_mnemonic(op: PixelOp) -> &'static str {
+    PIXEL_OPS
+        .iter()
+        .find(|(known, _)| *known == op)
+        .map_or("UNKNOWN", |(_, mnemonic)| mnemonic)
+}
+
+/// Executions `OpcodeCounter` keeps counts for before the oldest drop out
+pub const OPCODE_WINDOW: usize = 256;
+
+/// Executed steps per opcode, plus those that couldn't be attributed to one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpcodeCounts {
+    pub executed: HashMap<PixelOp, u64>,
+    /// Steps of runs whose path through the program isn't known; see `OpcodeCounter`
+    pub unattributed_steps: u64,
+}
+
+impl OpcodeCounts {
+    pub fn is_empty(&self) -> bool {
+        self.executed.is_empty() && self.unattributed_steps == 0
+    }
+}
+
+/// Executed-step counts over the last `OPCODE_WINDOW` executions, keyed by opcode.
+///
+/// The executor reports how many steps ran but not which instructions they were. A run that
+/// steps through no `JUMP` went straight down the program, so its steps are exactly the first
+/// `steps_executed` instructions and are counted per opcode. Any other run, and any opcode the
+/// VM doesn't define, adds to `unattributed_steps` instead.
+#[derive(Debug, Default)]
+pub struct OpcodeCounter {
+    window: Mutex<OpcodeWindow>,
+}
+
+#[derive(Debug, Default)]
+struct OpcodeWindow {
+    executions: VecDeque<OpcodeCounts>,
+    totals: OpcodeCounts,
+}
+
+impl OpcodeCounter {
+    /// Count the steps of a run of `program` that executed `steps_executed` steps
+    pub fn record(&self, program: &[PixelInstruction], steps_executed: u64) {
+        let mut counts = OpcodeCounts::default();
+        let straight_line = usize::try_from(steps_executed)
+            .ok()
+            .and_then(|steps| program.get(..steps))
+            .filter(|stepped| !stepped.iter().any(|i| i.r == PixelOp::JUMP as u8));
+        match straight_line {
+            Some(stepped) => {
+                for instruction in stepped {
+                    match pixel_op(instruction.r) {
+                        Some(op) => *counts.executed.entry(op).or_insert(0) += 1,
+                        None => counts.unattributed_steps += 1,
+                    }
+                }
+            }
+            None => counts.unattributed_steps = steps_executed,
+        }
+
+        let mut window = self.window.lock().unwrap();
+        for (&op, &count) in &counts.executed {
+            *window.totals.executed.entry(op).or_insert(0) += count;
+        }
+        window.totals.unattributed_steps += counts.unattributed_steps;
+        window.executions.push_back(counts);
+        if window.executions.len() > OPCODE_WINDOW {
+            let oldest = window.executions.pop_front().unwrap_or_default();
+            for (op, count) in oldest.executed {
+                if let Some(total) = window.totals.executed.get_mut(&op) {
+                    *total -= count;
+                    if *total == 0 {
+                        window.totals.executed.remove(&op);
+                    }
+                }
+            }
+            window.totals.unattributed_steps -= oldest.unattributed_steps;
+        }
+    }
+
+    /// Current counts; empty until something has executed
+    pub fn snapshot(&self) -> OpcodeCounts {
+        self.window.lock().unwrap().totals.clone()
+    }
+}
+
+/// Program length, in instructions, at which `ExecutionBackend::Auto` prefers the GPU
+pub const DEFAULT_AUTO_GPU_THRESHOLD: usize = 256;
 
 pub struct PixelVmRuntime {
     assembler: PixelAssembler,
+    auto_gpu_threshold: usize,
+    gpu_dispatch: GpuDispatchLimiter,
+    gpu_programs: ProgramTracker,
     #[cfg(feature = "gpu")]
     gpu_core: Option<Arc<gvpie_core::GpuCore>>,
 }
@@ -57,16 +336,33 @@ impl fmt::Debug for PixelVmRuntime {
 
         f.debug_struct("PixelVmRuntime")
             .field("gpu_available", &gpu_available)
+            .field("auto_gpu_threshold", &self.auto_gpu_threshold)
+            .field("gpu_dispatch", &self.gpu_dispatch)
             .finish()
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ExecutionBackend {
     #[default]
     Cpu,
     Gpu,
+    /// GPU for programs of at least the runtime's threshold when one is available, else CPU
+    Auto,
+}
+
+impl ExecutionBackend {
+    /// The concrete backend to run a program of `instructions` on; never returns `Auto`
+    pub fn resolve(self, instructions: usize, threshold: usize, gpu_available: bool) -> Self {
+        match self {
+            ExecutionBackend::Auto if gpu_available && instructions >= threshold => {
+                ExecutionBackend::Gpu
+            }
+            ExecutionBackend::Auto => ExecutionBackend::Cpu,
+            backend => backend,
+        }
+    }
 }
 
 impl PixelVmRuntime {
@@ -74,6 +370,9 @@ impl PixelVmRuntime {
     pub fn new(gpu_core: Option<Arc<gvpie_core::GpuCore>>) -> Self {
         Self {
             assembler: PixelAssembler::new(64, 64),
+            auto_gpu_threshold: DEFAULT_AUTO_GPU_THRESHOLD,
+            gpu_dispatch: GpuDispatchLimiter::default(),
+            gpu_programs: ProgramTracker::default(),
             gpu_core,
         }
     }
@@ -82,26 +381,78 @@ impl PixelVmRuntime {
     pub fn new(_gpu_core: Option<Arc<gvpie_core::GpuCore>>) -> Self {
         Self {
             assembler: PixelAssembler::new(64, 64),
+            auto_gpu_threshold: DEFAULT_AUTO_GPU_THRESHOLD,
+            gpu_dispatch: GpuDispatchLimiter::default(),
+            gpu_programs: ProgramTracker::default(),
         }
     }
 
+    /// Set the program length at which `ExecutionBackend::Auto` picks the GPU
+    pub fn with_auto_gpu_threshold(mut self, threshold: usize) -> Self {
+        self.auto_gpu_threshold = threshold;
+        self
+    }
+
+    /// Gate GPU executions on `limiter`; CPU executions are never limited
+    pub fn with_gpu_dispatch_limiter(mut self, limiter: GpuDispatchLimiter) -> Self {
+        self.gpu_dispatch = limiter;
+        self
+    }
+
+    /// Count programs against `tracker` while they run on the GPU
+    pub fn with_program_tracker(mut self, tracker: ProgramTracker) -> Self {
+        self.gpu_programs = tracker;
+        self
+    }
+
+    /// Resolve `Auto` against this runtime's threshold and GPU availability
+    pub fn resolve_backend(
+        &self,
+        backend: ExecutionBackend,
+        instructions: usize,
+    ) -> ExecutionBackend {
+        #[cfg(feature = "gpu")]
+        let gpu_available = self.gpu_core.is_some();
+        #[cfg(not(feature = "gpu"))]
+        let gpu_available = false;
+
+        backend.resolve(instructions, self.auto_gpu_threshold, gpu_available)
+    }
+
     pub async fn execute_program(
         &self,
         request: PixelProgramRequest,
-    ) -> Result<PixelProgramResponse> {
+    ) -> crate::Result<PixelProgramResponse> {
+        self.execute_program_cancellable(request, CancellationToken::new())
+            .await
+    }
+
+    /// Run `request` on a blocking thread, returning `PixelProgramResponse::error(CANCELLED)`
+    /// as soon as `cancel` fires instead of waiting for the executor to finish.
+    ///
+    /// Every call runs once on a freshly built `PixelExecutor`, so no machine state carries
+    /// over between executions.
+    pub async fn execute_program_cancellable(
+        &self,
+        request: PixelProgramRequest,
+        cancel: CancellationToken,
+    ) -> crate::Result<PixelProgramResponse> {
+        if cancel.is_cancelled() {
+            return Ok(PixelProgramResponse::error(CANCELLED));
+        }
+
         let start = Instant::now();
         let mut executor = PixelExecutor::new(request.canvas_width, request.canvas_height);
-        let preferred_backend = match request.backend {
-            ExecutionBackend::Cpu => PixelBackend::Cpu,
+        let preferred_backend = match self.resolve_backend(request.backend, request.program.len()) {
             ExecutionBackend::Gpu => PixelBackend::Gpu,
+            ExecutionBackend::Cpu | ExecutionBackend::Auto => PixelBackend::Cpu,
         };
 
         #[cfg(feature = "gpu")]
         if preferred_backend != PixelBackend::Cpu {
-            let gpu_core = self
-                .gpu_core
-                .as_ref()
-                .ok_or_else(|| anyhow!("GPU backend requested but no GPU core available"))?;
+            let gpu_core = self.gpu_core.as_ref().ok_or_else(|| {
+                AiRuntimeError::gpu_unavailable("GPU backend requested but no GPU core available")
+            })?;
             let gpu_executor =
                 GpuMachineExecutor::new(gpu_core.device().clone(), gpu_core.queue().clone())?;
             executor.enable_gpu(gpu_executor);
@@ -109,17 +460,64 @@ impl PixelVmRuntime {
 
         #[cfg(not(feature = "gpu"))]
         if preferred_backend == PixelBackend::Gpu {
-            return Err(anyhow!("GPU backend not supported in this build"));
+            return Err(AiRuntimeError::gpu_unavailable(
+                "GPU backend not supported in this build",
+            ));
+        }
+
+        // Nothing to run: report a blank canvas without dispatching to either backend
+        if request.program.is_empty() {
+            return Ok(PixelProgramResponse {
+                success: true,
+                cycles_executed: 0,
+                instruction_pointer: 0,
+                canvas_data: vec![
+                    0;
+                    request.canvas_width as usize * request.canvas_height as usize * 4
+                ],
+                execution_time_ms: start.elapsed().as_millis() as u64,
+                backend_used: preferred_backend.as_str().to_string(),
+                error: None,
+            });
         }
 
         executor.set_backend(preferred_backend);
+        let permit = if preferred_backend == PixelBackend::Gpu {
+            tokio::select! {
+                biased;
+                _ = cancel.cancelled() => return Ok(PixelProgramResponse::error(CANCELLED)),
+                permit = self.gpu_dispatch.acquire() => Some(permit?),
+            }
+        } else {
+            None
+        };
+        let PixelProgramRequest {
+            program,
+            max_cycles,
+            ..
+        } = request;
+        // The executor is synchronous, so it can't be interrupted mid-dispatch; dropping the
+        // join handle on cancel detaches it, and it releases its buffers and dispatch permit
+        // when it returns
+        let tracked = (preferred_backend == PixelBackend::Gpu).then(|| {
+            self.gpu_programs
+                .track(std::mem::size_of_val(&program[..]) as u64)
+        });
+        let execution = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let _tracked = tracked;
+            executor.execute_program(&program, max_cycles)
+        });
+        let outcome = tokio::select! {
+            biased;
+            _ = cancel.cancelled() => return Ok(PixelProgramResponse::error(CANCELLED)),
+            joined = execution => joined.map_err(|err| AiRuntimeError::internal(err.to_string()))?,
+        };
         let PixelExecutionOutcome {
             state,
             metadata,
             backend_used,
-        } = executor
-            .execute_program(&request.program, request.max_cycles)
-            .map_err(|err| anyhow!(err))?;
+        } = outcome.map_err(|err| anyhow!(err))?;
 
         let elapsed = start.elapsed();
         let canvas_data = Self::canvas_to_rgba(&state.canvas);
@@ -163,3 +561,270 @@ impl PixelVmRuntime {
         data
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identical_canvases_share_a_fingerprint() {
+        let canvas = vec![7u8; 8 * 8 * 4];
+        assert_eq!(
+            canvas_fingerprint(&canvas),
+            canvas_fingerprint(&canvas.clone())
+        );
+        assert_eq!(canvas_fingerprint(&[]), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn one_pixel_change_alters_the_fingerprint() {
+        let canvas = vec![0u8; 8 * 8 * 4];
+        let mut changed = canvas.clone();
+        changed[5 * 4] = 200;
+        assert_ne!(canvas_fingerprint(&canvas), canvas_fingerprint(&changed));
+    }
+
+    #[test]
+    fn white_canvas_renders_the_densest_glyph() {
+        let white = vec![255u8; 8 * 8 * 4];
+        assert_eq!(canvas_to_ascii(&white, 8, 8, 4), "@@@@\n@@@@\n");
+        assert_eq!(canvas_to_ascii(&white, 8, 8, 100), "@@@@@@@@\n".repeat(4));
+    }
+
+    #[test]
+    fn transparent_canvas_renders_spaces() {
+        let transparent = [255, 255, 255, 0].repeat(8 * 8);
+        assert_eq!(canvas_to_ascii(&transparent, 8, 8, 4), "    \n    \n");
+        assert_eq!(canvas_to_ascii(&transparent[..16], 8, 8, 4), "");
+    }
+
+    #[test]
+    fn opcode_counter_rolls_over_old_executions() {
+        let op = |op: PixelOp| PixelInstruction::new(op as u8, 0, 0, 0);
+        let counter = OpcodeCounter::default();
+        assert!(counter.snapshot().is_empty());
+
+        counter.record(&[op(PixelOp::SET), op(PixelOp::SET), op(PixelOp::HALT)], 3);
+        assert_eq!(
+            counter.snapshot().executed,
+            HashMap::from([(PixelOp::SET, 2), (PixelOp::HALT, 1)])
+        );
+
+        for _ in 0..OPCODE_WINDOW {
+            counter.record(&[op(PixelOp::FILL)], 1);
+        }
+        assert_eq!(
+            counter.snapshot(),
+            OpcodeCounts {
+                executed: HashMap::from([(PixelOp::FILL, OPCODE_WINDOW as u64)]),
+                unattributed_steps: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn runs_that_jump_are_not_attributed_by_opcode() {
+        let counter = OpcodeCounter::default();
+        let program = [
+            PixelInstruction::new(PixelOp::FILL as u8, 0, 1, 0),
+            PixelInstruction::new(PixelOp::JUMP as u8, 0, 0, 0),
+        ];
+
+        // The first step stops short of the jump, so it is known to be the FILL
+        counter.record(&program, 1);
+        // Ten steps of a two-instruction program: the jump looped, order unknown
+        counter.record(&program, 10);
+        assert_eq!(
+            counter.snapshot(),
+            OpcodeCounts {
+                executed: HashMap::from([(PixelOp::FILL, 1)]),
+                unattributed_steps: 10,
+            }
+        );
+        assert_eq!(op_mnemonic(PixelOp::SET), "PUTPIX");
+        assert_eq!(pixel_op(PixelOp::JUMP as u8), Some(PixelOp::JUMP));
+    }
+
+    #[test]
+    fn differing_pixels_reports_coordinates() {
+        let a = vec![0u8; 4 * 4 * 4];
+        let mut b = a.clone();
+        b[(2 * 4 + 1) * 4 + 3] = 1;
+        b[(3 * 4) * 4] = 1;
+
+        assert!(differing_pixels(&a, &a, 4).is_empty());
+        assert_eq!(differing_pixels(&a, &b, 4), vec![(1, 2), (0, 3)]);
+    }
+
+    fn request(program: Vec<PixelInstruction>) -> PixelProgramRequest {
+        PixelProgramRequest {
+            program,
+            backend: ExecutionBackend::Cpu,
+            max_cycles: u64::MAX,
+            canvas_width: 8,
+            canvas_height: 8,
+        }
+    }
+
+    /// 4x3 canvas whose pixel at (x, y) is [x, y, 0, 255]
+    fn coordinate_canvas() -> Vec<u8> {
+        (0..3u8)
+            .flat_map(|y| (0..4u8).flat_map(move |x| [x, y, 0, 255]))
+            .collect()
+    }
+
+    #[test]
+    fn canvas_region_matches_full_canvas_rows() {
+        let canvas = coordinate_canvas();
+        let region: CanvasRegion = "1,1,2,2".parse().unwrap();
+
+        let data = canvas_region(&canvas, 4, 3, region);
+        let row = |y: usize| &canvas[(y * 4 + 1) * 4..(y * 4 + 3) * 4];
+        assert_eq!(data, [row(1), row(2)].concat());
+        assert_eq!(canvas_region(&canvas, 4, 3, region.clamp(4, 3)), data);
+    }
+
+    #[test]
+    fn canvas_region_clamps_at_the_edges() {
+        let canvas = coordinate_canvas();
+        let region = |s: &str| s.parse::<CanvasRegion>().unwrap();
+
+        assert_eq!(region("2,1,10,10").clamp(4, 3), region("2,1,2,2"));
+        assert_eq!(
+            canvas_region(&canvas, 4, 3, region("3,2,5,5")),
+            vec![3, 2, 0, 255]
+        );
+        assert!(canvas_region(&canvas, 4, 3, region("9,9,2,2")).is_empty());
+        assert_eq!(canvas_region(&canvas, 4, 3, region("0,0,4,3")), canvas);
+        assert!(canvas_region(&[], 4, 3, region("0,0,2,2")).is_empty());
+        assert!(canvas_region(&canvas[..16], 4, 3, region("0,1,2,2")).is_empty());
+
+        assert!("1,2,3".parse::<CanvasRegion>().is_err());
+        assert!("a,b,c,d".parse::<CanvasRegion>().is_err());
+    }
+
+    #[test]
+    fn auto_backend_resolves_by_size_and_availability() {
+        let auto = ExecutionBackend::Auto;
+        assert_eq!(auto.resolve(3, 256, true), ExecutionBackend::Cpu);
+        assert_eq!(auto.resolve(256, 256, true), ExecutionBackend::Gpu);
+        assert_eq!(auto.resolve(10_000, 256, false), ExecutionBackend::Cpu);
+        assert_eq!(
+            ExecutionBackend::Gpu.resolve(3, 256, false),
+            ExecutionBackend::Gpu
+        );
+        assert_eq!(
+            ExecutionBackend::Cpu.resolve(10_000, 256, true),
+            ExecutionBackend::Cpu
+        );
+    }
+
+    #[tokio::test]
+    async fn auto_falls_back_to_cpu_without_gpu() {
+        let runtime = PixelVmRuntime::new(None).with_auto_gpu_threshold(4);
+        let mut program = vec![PixelInstruction::new(gvpie_core::PixelOp::SET as u8, 1, 9, 0); 8];
+        program.push(PixelInstruction::new(
+            gvpie_core::PixelOp::HALT as u8,
+            0,
+            0,
+            0,
+        ));
+        let request = PixelProgramRequest {
+            backend: ExecutionBackend::Auto,
+            ..request(program)
+        };
+
+        let response = runtime.execute_program(request).await.unwrap();
+        assert!(response.success);
+        assert_eq!(response.backend_used, "cpu");
+    }
+
+    #[tokio::test]
+    async fn empty_program_returns_a_blank_canvas() {
+        let runtime = PixelVmRuntime::new(None);
+        let request = PixelProgramRequest {
+            canvas_width: 3,
+            canvas_height: 2,
+            ..request(Vec::new())
+        };
+
+        let response = runtime.execute_program(request).await.unwrap();
+        assert!(response.success);
+        assert_eq!(response.cycles_executed, 0);
+        assert_eq!(response.instruction_pointer, 0);
+        assert_eq!(response.canvas_data, vec![0; 3 * 2 * 4]);
+        assert_eq!(response.backend_used, "cpu");
+    }
+
+    #[tokio::test]
+    async fn cancelled_execution_returns_without_running() {
+        let runtime = PixelVmRuntime::new(None);
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let program = vec![PixelInstruction::new(0, 0, 0, 0); 1 << 20];
+        let response = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            runtime.execute_program_cancellable(request(program), cancel),
+        )
+        .await
+        .expect("cancelled execution should not block")
+        .unwrap();
+
+        assert!(!response.success);
+        assert_eq!(response.error.as_deref(), Some(CANCELLED));
+    }
+
+    #[tokio::test]
+    async fn cancelling_mid_run_returns_before_the_executor_finishes() {
+        let runtime = PixelVmRuntime::new(None);
+        let cancel = CancellationToken::new();
+        let program = vec![PixelInstruction::new(0, 0, 0, 0); 1 << 24];
+
+        let execution = runtime.execute_program_cancellable(request(program), cancel.clone());
+        let canceller = async {
+            tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            cancel.cancel();
+        };
+        let (response, ()) = tokio::join!(execution, canceller);
+
+        let response = response.unwrap();
+        assert_eq!(response.error.as_deref(), Some(CANCELLED));
+    }
+
+    #[tokio::test]
+    async fn executions_do_not_share_machine_state() {
+        let runtime = PixelVmRuntime::new(None);
+        let set = PixelInstruction::new(gvpie_core::PixelOp::SET as u8, 5, 200, 0);
+        let halt = PixelInstruction::new(gvpie_core::PixelOp::HALT as u8, 0, 0, 0);
+
+        let first = runtime
+            .execute_program(request(vec![set, halt]))
+            .await
+            .unwrap();
+        assert_eq!(first.canvas_data[5 * 4], 200);
+
+        let second = runtime.execute_program(request(vec![halt])).await.unwrap();
+        assert_eq!(second.canvas_data, vec![0; 8 * 8 * 4]);
+        assert_eq!(second.cycles_executed, 1);
+    }
+
+    #[tokio::test]
+    async fn uncancelled_execution_completes() {
+        let runtime = PixelVmRuntime::new(None);
+        let program = vec![PixelInstruction::new(
+            gvpie_core::PixelOp::HALT as u8,
+            0,
+            0,
+            0,
+        )];
+
+        let response = runtime
+            .execute_program_cancellable(request(program), CancellationToken::new())
+            .await
+            .unwrap();
+
+        assert!(response.success);
+        assert_eq!(response.canvas_data.len(), 8 * 8 * 4);
+    }
+}
@@ -0,0 +1,168 @@
+//! End-to-end diagnostic behind `gvpie_dev_assistant selftest`
+//!
+//! Runs a fixed pixel program on the CPU backend and, when a GPU core is
+//! available, on the GPU backend as well, then compares the two canvases, and
+//! runs the analyzer over a sample component. The report is meant to be
+//! attached to GPU bug reports as-is.
+//!
+//! Adapter name and backend are not reported: `GpuCore` exposes only its wgpu
+//! device and queue, and neither carries the adapter info, so `adapter` stays
+//! `None` until `gvpie-core` hands it out.
+
+use crate::gpu_bridge::GpuMemoryReport;
+use gvpie_core::{PixelInstruction, PixelOp};
+use serde::{Deserialize, Serialize};
+
+pub const SELFTEST_CANVAS_SIZE: u32 = 16;
+pub const SELFTEST_MAX_CYCLES: u64 = 64;
+/// Component the analyzer check runs on; component analysis works from the path alone
+pub const SAMPLE_COMPONENT: &str = "gvpie-core/src/gpu_executor.rs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelftestCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl SelftestCheck {
+    pub fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelftestOutcome {
+    /// Both backends ran and agreed
+    Pass,
+    /// The CPU backend passed; no GPU was available to compare against
+    CpuOnly,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelftestReport {
+    pub outcome: SelftestOutcome,
+    pub version: String,
+    pub backends: Vec<String>,
+    pub gpu_memory: GpuMemoryReport,
+    /// Adapter name and backend; always `None` while `GpuCore` does not expose them
+    pub adapter: Option<String>,
+    pub checks: Vec<SelftestCheck>,
+}
+
+impl SelftestReport {
+    pub fn new(
+        backends: Vec<String>,
+        gpu_memory: GpuMemoryReport,
+        checks: Vec<SelftestCheck>,
+    ) -> Self {
+        let outcome = if checks.iter().any(|c| c.status == CheckStatus::Fail) {
+            SelftestOutcome::Fail
+        } else if checks.iter().any(|c| c.status == CheckStatus::Skipped) {
+            SelftestOutcome::CpuOnly
+        } else {
+            SelftestOutcome::Pass
+        };
+        Self {
+            outcome,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            backends,
+            gpu_memory,
+            adapter: None,
+            checks,
+        }
+    }
+
+    /// Process exit code: 0 on pass, 1 on failure, 2 when the GPU path could not be exercised
+    pub fn exit_code(&self) -> i32 {
+        match self.outcome {
+            SelftestOutcome::Pass => 0,
+            SelftestOutcome::Fail => 1,
+            SelftestOutcome::CpuOnly => 2,
+        }
+    }
+}
+
+/// Known program lighting two pixels, shared by both backends
+pub fn sample_program() -> Vec<PixelInstruction> {
+    vec![
+        PixelInstruction::new(PixelOp::SET as u8, 5, 200, 0),
+        PixelInstruction::new(PixelOp::SET as u8, 9, 17, 0),
+        PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0),
+    ]
+}
+
+/// Compare two RGBA canvases, describing the first differing pixel
+pub fn compare_canvases(cpu: &[u8], gpu: &[u8]) -> std::result::Result<(), String> {
+    if cpu.len() != gpu.len() {
+        return Err(format!(
+            "canvas sizes differ: cpu {} bytes, gpu {} bytes",
+            cpu.len(),
+            gpu.len()
+        ));
+    }
+    let differing = crate::pixel_vm::differing_pixels(cpu, gpu, SELFTEST_CANVAS_SIZE);
+    let Some(&(x, y)) = differing.first() else {
+        return Ok(());
+    };
+    let offset = (y * SELFTEST_CANVAS_SIZE + x) as usize * 4;
+    Err(format!(
+        "{} pixels differ; first at ({}, {}): cpu {:?}, gpu {:?}",
+        differing.len(),
+        x,
+        y,
+        &cpu[offset..offset + 4],
+        &gpu[offset..offset + 4]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outcome_follows_check_statuses() {
+        let report = |statuses: &[CheckStatus]| {
+            let checks = statuses
+                .iter()
+                .map(|&status| SelftestCheck::new("check", status, ""))
+                .collect();
+            SelftestReport::new(Vec::new(), GpuMemoryReport::default(), checks)
+        };
+        assert_eq!(report(&[CheckStatus::Pass]).exit_code(), 0);
+        assert_eq!(
+            report(&[CheckStatus::Pass, CheckStatus::Skipped]).outcome,
+            SelftestOutcome::CpuOnly
+        );
+        assert_eq!(
+            report(&[CheckStatus::Fail, CheckStatus::Skipped]).exit_code(),
+            1
+        );
+    }
+
+    #[test]
+    fn canvas_mismatch_names_the_first_pixel() {
+        let cpu = vec![0u8; 32 * 4];
+        let mut gpu = cpu.clone();
+        gpu[(SELFTEST_CANVAS_SIZE as usize + 3) * 4] = 9;
+
+        assert!(compare_canvases(&cpu, &cpu).is_ok());
+        let err = compare_canvases(&cpu, &gpu).unwrap_err();
+        assert!(err.contains("(3, 1)"), "{err}");
+        assert!(compare_canvases(&cpu, &gpu[4..]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use ai_runtime::{AiRuntime, ExecutionBackend, PixelProgramRequest};
+use ai_runtime::{AiRuntime, AiRuntimeError, ExecutionBackend, PixelProgramRequest};
 use axum::{
     body::Body,
     http::{Request, StatusCode},
@@ -12,6 +12,7 @@ use tower::ServiceExt; // for `oneshot`
 async fn test_runtime_initialization() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
     let runtime = AiRuntime::new().await;
     assert!(runtime.is_ok());
@@ -22,6 +23,7 @@ async fn test_runtime_initialization() {
 async fn test_gpu_detection() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
     let runtime = AiRuntime::new().await.unwrap();
     // This will be false in CI without GPU, but that's OK
@@ -33,6 +35,7 @@ async fn test_gpu_detection() {
 async fn test_cartridge_crud_operations() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
 
     let runtime = AiRuntime::new().await.unwrap();
@@ -43,6 +46,7 @@ async fn test_cartridge_crud_operations() {
         name: "Test CRUD".to_string(),
         description: "Testing CRUD operations".to_string(),
         code: "test code".to_string(),
+        code_format: Default::default(),
         version: "1.0.0".to_string(),
         author: Some("Test".to_string()),
         tags: vec!["test".to_string()],
@@ -62,6 +66,7 @@ async fn test_cartridge_crud_operations() {
         name: "Updated Test".to_string(),
         description: "Updated description".to_string(),
         code: "updated code".to_string(),
+        code_format: Default::default(),
         version: "2.0.0".to_string(),
         author: Some("Test".to_string()),
         tags: vec!["test".to_string()],
@@ -84,6 +89,7 @@ async fn test_cartridge_crud_operations() {
 async fn test_api_cartridge_management() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
 
     let runtime = AiRuntime::new().await.unwrap();
@@ -133,6 +139,7 @@ async fn test_api_cartridge_management() {
 async fn test_gpu_execution_reporting() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
 
     let runtime = AiRuntime::new().await.unwrap();
@@ -154,6 +161,7 @@ async fn test_gpu_execution_reporting() {
 async fn test_pixel_vm_cpu_execution() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
 
     let runtime = AiRuntime::new().await.unwrap();
@@ -184,6 +192,7 @@ async fn test_pixel_vm_cpu_execution() {
 async fn test_api_pixel_execute() {
     let temp_dir = tempfile::tempdir().unwrap();
     std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
     std::env::set_var("GVPIE_DISABLE_GPU", "1");
 
     let runtime = AiRuntime::new().await.unwrap();
@@ -222,3 +231,1165 @@ async fn test_api_pixel_execute() {
     assert!(body.success);
     assert_eq!(body.backend_used, "cpu");
 }
+
+#[tokio::test]
+#[serial]
+async fn test_api_pixel_execute_fingerprint_header() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let app = ai_runtime::api::ApiServer::router(std::sync::Arc::new(runtime));
+
+    let program = vec![
+        PixelInstruction::new(PixelOp::SET as u8, 5, 200, 0),
+        PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0),
+    ];
+
+    let payload = serde_json::to_string(&serde_json::json!({
+        "program": program,
+        "backend": "cpu",
+        "max_cycles": 50,
+        "canvas_width": 8,
+        "canvas_height": 8
+    }))
+    .unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/pixel/run")
+                .header("Content-Type", "application/json")
+                .body(Body::from(payload))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let fingerprint = response
+        .headers()
+        .get(ai_runtime::CANVAS_FINGERPRINT_HEADER)
+        .expect("fingerprint header")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: ai_runtime::PixelProgramResponse = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(
+        fingerprint,
+        format!("{:016x}", ai_runtime::canvas_fingerprint(&body.canvas_data))
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_pixel_execute_packed_bytes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let app = ai_runtime::api::ApiServer::router(std::sync::Arc::new(runtime));
+
+    let program = vec![
+        PixelInstruction::new(PixelOp::SET as u8, 5, 200, 0),
+        PixelInstruction::new(PixelOp::SET as u8, 9, 17, 0),
+        PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0),
+    ];
+    let packed: Vec<u8> = program.iter().flat_map(|i| [i.r, i.g, i.b, i.a]).collect();
+    let json = serde_json::to_string(&serde_json::json!({
+        "program": program,
+        "backend": "cpu",
+        "max_cycles": 50,
+        "canvas_width": 8,
+        "canvas_height": 8
+    }))
+    .unwrap();
+
+    let requests = [
+        Request::builder()
+            .method("POST")
+            .uri("/api/pixel/run")
+            .header("Content-Type", "application/json")
+            .body(Body::from(json))
+            .unwrap(),
+        Request::builder()
+            .method("POST")
+            .uri("/api/pixel/run?backend=cpu&max_cycles=50&canvas_width=8&canvas_height=8")
+            .header("Content-Type", "application/octet-stream")
+            .body(Body::from(packed))
+            .unwrap(),
+    ];
+    let mut bodies = Vec::new();
+    for request in requests {
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: ai_runtime::PixelProgramResponse = serde_json::from_slice(&bytes).unwrap();
+        assert!(body.success);
+        bodies.push(body);
+    }
+    assert_eq!(bodies[0].cycles_executed, bodies[1].cycles_executed);
+    assert_eq!(bodies[0].instruction_pointer, bodies[1].instruction_pointer);
+    assert_eq!(bodies[0].canvas_data, bodies[1].canvas_data);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/pixel/run")
+                .header("Content-Type", "application/octet-stream")
+                .body(Body::from(vec![1u8, 2, 3]))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_selftest_reports_cpu_only_without_gpu() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let report = runtime.selftest().await;
+
+    assert_eq!(report.outcome, ai_runtime::SelftestOutcome::CpuOnly);
+    assert_eq!(report.exit_code(), 2);
+    assert_eq!(report.backends, vec!["cpu".to_string()]);
+    assert_eq!(report.adapter, None);
+    let status = |name: &str| {
+        report
+            .checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| check.status)
+    };
+    assert_eq!(
+        status("cpu_execution"),
+        Some(ai_runtime::selftest::CheckStatus::Pass)
+    );
+    assert_eq!(
+        status("gpu_execution"),
+        Some(ai_runtime::selftest::CheckStatus::Skipped)
+    );
+    assert_eq!(
+        status("analyzer"),
+        Some(ai_runtime::selftest::CheckStatus::Pass)
+    );
+    assert!(runtime.opcode_counts().is_empty());
+}
+
+#[cfg(feature = "gpu")]
+#[tokio::test]
+#[serial]
+async fn test_diff_backends_identical_program_has_no_divergence() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::remove_var("GVPIE_DISABLE_GPU");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+    if !runtime.gpu_available() {
+        eprintln!("skipping: no GPU available");
+        return;
+    }
+
+    let diff = runtime
+        .diff_backends(
+            ai_runtime::selftest::sample_program(),
+            8,
+            8,
+            50,
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    assert!(!diff.diverged);
+    assert_eq!(diff.first_divergent_step, None);
+    assert!(diff.differing_pixels.is_empty());
+    assert_eq!(diff.cpu_cycles, diff.gpu_cycles);
+}
+
+fn auto_request(instructions: usize) -> PixelProgramRequest {
+    let mut program: Vec<_> = (0..instructions)
+        .map(|i| PixelInstruction::new(PixelOp::SET as u8, (i % 64) as u8, 1, 0))
+        .collect();
+    program.push(PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0));
+    PixelProgramRequest {
+        program,
+        backend: ExecutionBackend::Auto,
+        max_cycles: 4096,
+        canvas_width: 8,
+        canvas_height: 8,
+    }
+}
+
+#[cfg(feature = "gpu")]
+#[tokio::test]
+#[serial]
+async fn test_auto_backend_selects_by_program_size() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::remove_var("GVPIE_DISABLE_GPU");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+    if !runtime.gpu_available() {
+        eprintln!("skipping: no GPU available");
+        return;
+    }
+
+    let tiny = runtime
+        .execute_pixel_program(auto_request(2))
+        .await
+        .unwrap();
+    assert_eq!(tiny.backend_used, "cpu");
+
+    let large = auto_request(ai_runtime::pixel_vm::DEFAULT_AUTO_GPU_THRESHOLD);
+    let large = runtime.execute_pixel_program(large).await.unwrap();
+    assert!(large.success);
+    assert_eq!(large.backend_used, "gpu");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_auto_backend_without_gpu_runs_on_cpu() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let large = auto_request(ai_runtime::pixel_vm::DEFAULT_AUTO_GPU_THRESHOLD * 2);
+    let response = runtime.execute_pixel_program(large).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.backend_used, "cpu");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_pixel_diff_without_gpu_is_unavailable() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let app = ai_runtime::api::ApiServer::router(std::sync::Arc::new(runtime));
+    let payload = serde_json::json!({ "program": ai_runtime::selftest::sample_program() });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/pixel/diff")
+                .header("Content-Type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_gpu_backend_without_gpu_is_unavailable() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let program = vec![PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0)];
+
+    let request = PixelProgramRequest {
+        program: program.clone(),
+        backend: ExecutionBackend::Gpu,
+        max_cycles: 10,
+        canvas_width: 8,
+        canvas_height: 8,
+    };
+    let err = runtime.execute_pixel_program(request).await.unwrap_err();
+    assert!(matches!(err, AiRuntimeError::GpuUnavailable(_)));
+
+    let app = ai_runtime::api::ApiServer::router(std::sync::Arc::new(runtime));
+    let payload = serde_json::to_string(&serde_json::json!({
+        "program": program,
+        "backend": "gpu",
+        "max_cycles": 10,
+        "canvas_width": 8,
+        "canvas_height": 8
+    }))
+    .unwrap();
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/pixel/run")
+                .header("Content-Type", "application/json")
+                .body(Body::from(payload))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: ai_runtime::PixelProgramResponse = serde_json::from_slice(&bytes).unwrap();
+    assert!(!body.success);
+}
+
+async fn get_health(runtime: AiRuntime) -> (StatusCode, serde_json::Value) {
+    let app = ai_runtime::api::ApiServer::router(std::sync::Arc::new(runtime));
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/health")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_health_all_components_healthy() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path().join("cartridges"));
+    std::env::set_var("GVPIE_LOG_DIR", temp_dir.path().join("logs"));
+    std::env::set_var("GVPIE_DB_PATH", temp_dir.path().join("gvpie.db"));
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let (status, body) = get_health(runtime).await;
+    std::env::remove_var("GVPIE_LOG_DIR");
+    std::env::remove_var("GVPIE_DB_PATH");
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["components"]["database"]["status"], "ok");
+    assert_eq!(body["components"]["logs"]["status"], "ok");
+    assert_eq!(body["components"]["gpu"]["status"], "disabled");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_health_unwritable_log_dir_is_degraded() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    // A regular file where the log directory should be: unwritable even as root.
+    let log_dir = temp_dir.path().join("logs");
+    std::fs::write(&log_dir, b"not a directory").unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path().join("cartridges"));
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_LOG_DIR", &log_dir);
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let (status, body) = get_health(runtime).await;
+    std::env::remove_var("GVPIE_LOG_DIR");
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "degraded");
+    assert_eq!(body["components"]["logs"]["status"], "degraded");
+    assert_eq!(body["components"]["database"]["status"], "ok");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_cartridge_history_and_replay() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    runtime
+        .create_cartridge(ai_runtime::cartridges::Cartridge {
+            id: "replayable".to_string(),
+            name: "Replayable".to_string(),
+            description: "History test".to_string(),
+            code: "emit 42".to_string(),
+            code_format: Default::default(),
+            version: "1.0.0".to_string(),
+            author: None,
+            tags: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    let first = runtime
+        .execute_cartridge("replayable", Some("alpha"))
+        .await
+        .unwrap();
+    runtime
+        .execute_cartridge("replayable", Some("beta"))
+        .await
+        .unwrap();
+
+    let history = runtime.cartridge_history("replayable").await.unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].input.as_deref(), Some("alpha"));
+    assert_eq!(history[1].input.as_deref(), Some("beta"));
+
+    let (record, replayed) = runtime.replay_cartridge("replayable", 0).await.unwrap();
+    assert_eq!(record.input.as_deref(), Some("alpha"));
+    assert_eq!(replayed.output, first.output);
+    assert_eq!(replayed.data, first.data);
+    assert_eq!(
+        runtime.cartridge_history("replayable").await.unwrap().len(),
+        3
+    );
+    assert!(runtime.replay_cartridge("replayable", 10).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[serial]
+async fn test_slow_execution_does_not_block_cartridge_writes() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_path = temp_dir.path().join("gvpie.db");
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path().join("cartridges"));
+    std::env::remove_var("GVPIE_DB_PATH");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    // The execution's database write waits out the busy timeout while the blocker holds
+    // the lock; keep it short but well past the checks below.
+    let config = ai_runtime::config::Config {
+        database_url: format!("sqlite:{}", db_path.display()),
+        database: ai_runtime::config::DatabaseConfig {
+            busy_timeout_ms: 1_000,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let runtime = std::sync::Arc::new(AiRuntime::with_config(config).await.unwrap());
+    let cartridge = |id: &str| ai_runtime::cartridges::Cartridge {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: "Lock test".to_string(),
+        code: "noop".to_string(),
+        code_format: Default::default(),
+        version: "1.0.0".to_string(),
+        author: None,
+        tags: Vec::new(),
+    };
+    runtime.create_cartridge(cartridge("slow")).await.unwrap();
+
+    // Hold the database so the execution stalls after running the cartridge.
+    let blocker = rusqlite::Connection::open(&db_path).unwrap();
+    blocker.execute_batch("BEGIN EXCLUSIVE").unwrap();
+
+    let execution = tokio::spawn({
+        let runtime = runtime.clone();
+        async move { runtime.execute_cartridge("slow", Some("input")).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert!(!execution.is_finished(), "execution should be stalled");
+
+    let created = tokio::time::timeout(
+        std::time::Duration::from_millis(500),
+        runtime.create_cartridge(cartridge("concurrent")),
+    )
+    .await;
+    assert!(created.is_ok(), "create_cartridge blocked behind execution");
+
+    blocker.execute_batch("COMMIT").unwrap();
+    execution.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+#[serial]
+async fn test_cartridge_code_format_selects_assembler() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let cartridge = |id: &str, code: &str, code_format| ai_runtime::Cartridge {
+        id: id.to_string(),
+        name: id.to_string(),
+        description: "Format test".to_string(),
+        code: code.to_string(),
+        code_format,
+        version: "1.0.0".to_string(),
+        author: None,
+        tags: Vec::new(),
+    };
+    let as_json = |program| serde_json::to_value(program).unwrap();
+
+    let raw = cartridge(
+        "raw",
+        "01020304 ff000000",
+        ai_runtime::CodeFormat::RawPixels,
+    );
+    let from_pixels = runtime.assemble_cartridge(&raw).unwrap().unwrap();
+    assert_eq!(from_pixels.len(), 2);
+    assert_eq!(
+        as_json(from_pixels),
+        as_json(
+            gvpie_core::PixelAssembler::new(64, 64)
+                .assemble_from_pixels(&[[0x01, 0x02, 0x03, 0x04], [0xff, 0x00, 0x00, 0x00]])
+        )
+    );
+
+    let source = "SET 0 255\nHALT";
+    let asm = cartridge("asm", source, ai_runtime::CodeFormat::PixelAsm);
+    assert_eq!(
+        as_json(runtime.assemble_cartridge(&asm).unwrap().unwrap()),
+        as_json(runtime.assemble_pixel_program(source).unwrap())
+    );
+
+    let binary = cartridge("bin", "\x7fELF", ai_runtime::CodeFormat::Binary);
+    assert!(runtime.assemble_cartridge(&binary).unwrap().is_none());
+    let bad = cartridge("bad", "0102", ai_runtime::CodeFormat::RawPixels);
+    assert!(runtime.assemble_cartridge(&bad).is_err());
+
+    runtime.create_cartridge(raw).await.unwrap();
+    let result = runtime.execute_cartridge("raw", None).await.unwrap();
+    assert_eq!(result.instructions, 2);
+
+    // Cartridges saved before code_format existed still load as pixel assembly.
+    let legacy: ai_runtime::Cartridge = serde_json::from_str(
+        r#"{"id":"old","name":"Old","description":"","code":"HALT","version":"1.0.0","author":null,"tags":[]}"#,
+    )
+    .unwrap();
+    assert_eq!(legacy.code_format, ai_runtime::CodeFormat::PixelAsm);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_cartridge_search_endpoint() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = std::sync::Arc::new(AiRuntime::new().await.unwrap());
+    let app = ai_runtime::api::ApiServer::router(runtime.clone());
+    let search = |app: axum::Router, q: &str| {
+        let uri = format!("/api/cartridges/search?q={}", q);
+        async move {
+            let response = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let results: Vec<ai_runtime::Cartridge> = serde_json::from_slice(&bytes).unwrap();
+            results.into_iter().map(|c| c.id).collect::<Vec<_>>()
+        }
+    };
+
+    // "rain" only appears in the matrix_display description
+    assert_eq!(search(app.clone(), "rain").await, vec!["matrix_display"]);
+    assert_eq!(search(app.clone(), "glyph").await[0], "glyph_expander");
+
+    runtime.delete_cartridge("matrix_display").await.unwrap();
+    assert!(search(app.clone(), "rain").await.is_empty());
+    assert!(runtime.search_cartridges("rain").await.is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_cartridge_execution_cache() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    let cartridge = |code: &str| ai_runtime::Cartridge {
+        id: "cached".to_string(),
+        name: "Cached".to_string(),
+        description: "Cache test".to_string(),
+        code: code.to_string(),
+        code_format: ai_runtime::CodeFormat::Binary,
+        version: "1.0.0".to_string(),
+        author: None,
+        tags: Vec::new(),
+    };
+    runtime.create_cartridge(cartridge("v1")).await.unwrap();
+
+    let first = runtime
+        .execute_cartridge("cached", Some("a"))
+        .await
+        .unwrap();
+    assert!(!first.from_cache);
+    let hit = runtime
+        .execute_cartridge("cached", Some("a"))
+        .await
+        .unwrap();
+    assert!(hit.from_cache);
+    assert_eq!(hit.data, first.data);
+
+    // Different inputs (including no input) are cached separately
+    assert!(
+        !runtime
+            .execute_cartridge("cached", Some("b"))
+            .await
+            .unwrap()
+            .from_cache
+    );
+    assert!(
+        !runtime
+            .execute_cartridge("cached", None)
+            .await
+            .unwrap()
+            .from_cache
+    );
+    assert!(
+        runtime
+            .execute_cartridge("cached", Some("b"))
+            .await
+            .unwrap()
+            .from_cache
+    );
+
+    // Updating the cartridge, even without a version bump, invalidates it
+    runtime.update_cartridge(cartridge("v2")).await.unwrap();
+    let updated = runtime
+        .execute_cartridge("cached", Some("a"))
+        .await
+        .unwrap();
+    assert!(!updated.from_cache);
+    assert_eq!(updated.data, b"v2");
+
+    runtime.delete_cartridge("cached").await.unwrap();
+    assert!(runtime
+        .execute_cartridge("cached", Some("a"))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_stats_endpoint_aggregates_window() {
+    use ai_runtime::{CartridgeExecutionRecord, DecisionRecord, EventRecord, SystemMetricsRecord};
+    use chrono::{Duration, Utc};
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = std::sync::Arc::new(AiRuntime::new().await.unwrap());
+    let db = runtime.database();
+    let now = Utc::now();
+
+    // (hours ago, cpu); the 30h sample falls outside the default 24h window
+    for (hours_ago, cpu) in [(1, 20.0), (5, 40.0), (30, 90.0)] {
+        let metrics = SystemMetricsRecord {
+            recorded_at: now - Duration::hours(hours_ago),
+            cpu: Some(cpu),
+            memory: Some(50.0),
+            disk: Some(10.0),
+            state_json: serde_json::json!({}),
+        };
+        db.log_metrics(&metrics).await.unwrap();
+    }
+    for (hours_ago, action) in [
+        (2, "scale_up"),
+        (3, "scale_up"),
+        (4, "restart"),
+        (48, "restart"),
+    ] {
+        let decision = DecisionRecord {
+            decided_at: now - Duration::hours(hours_ago),
+            action: Some(action.to_string()),
+            confidence: None,
+            decision_json: serde_json::json!({}),
+            state_json: serde_json::json!({}),
+        };
+        db.log_decision(&decision).await.unwrap();
+    }
+    for (hours_ago, kind) in [(1, "alert"), (6, "alert"), (2, "deploy"), (72, "deploy")] {
+        let event = EventRecord {
+            kind: kind.to_string(),
+            payload_json: serde_json::json!({}),
+            created_at: now - Duration::hours(hours_ago),
+        };
+        db.record_event(&event).await.unwrap();
+    }
+    for (hours_ago, cartridge) in [(1, "a"), (2, "a"), (3, "b"), (40, "b")] {
+        let record = CartridgeExecutionRecord {
+            cartridge_id: cartridge.to_string(),
+            input: None,
+            executed_at: now - Duration::hours(hours_ago),
+            result_hash: String::new(),
+        };
+        db.record_cartridge_execution(&record, 10).await.unwrap();
+    }
+
+    let app = ai_runtime::api::ApiServer::router(runtime.clone());
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let stats: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+    assert_eq!(stats["window_hours"], 24);
+    assert_eq!(stats["resources"]["cpu_avg"], 30.0);
+    assert_eq!(stats["resources"]["memory_avg"], 50.0);
+    assert_eq!(
+        stats["decisions_by_action"],
+        serde_json::json!({ "scale_up": 2, "restart": 1 })
+    );
+    assert_eq!(
+        stats["events_by_kind"],
+        serde_json::json!({ "alert": 2, "deploy": 1 })
+    );
+    assert_eq!(stats["cartridge_executions"], 3);
+    assert_eq!(
+        stats["executions_by_cartridge"],
+        serde_json::json!({ "a": 2, "b": 1 })
+    );
+
+    let wide = runtime.stats(100).await.unwrap();
+    assert_eq!(wide.resources.cpu_avg, 50.0);
+    assert_eq!(wide.decisions_by_action["restart"], 2);
+    assert_eq!(wide.cartridge_executions, 4);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats?hours=0")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/stats?hours={}", i64::MAX))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(runtime
+        .stats(ai_runtime::MAX_STATS_HOURS + 1)
+        .await
+        .is_err());
+}
+
+/// Shared buffer a test subscriber writes formatted log lines into
+#[derive(Clone, Default)]
+struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+impl std::io::Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn test_startup_logs_capability_report() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let db_path = temp_dir.path().join("startup.db");
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+    std::env::set_var("GVPIE_DB_PATH", &db_path);
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_ansi(false)
+        .with_writer({
+            let logs = logs.clone();
+            move || logs.clone()
+        })
+        .finish();
+    let runtime = {
+        let _guard = tracing::subscriber::set_default(subscriber);
+        AiRuntime::new().await
+    };
+    std::env::remove_var("GVPIE_DB_PATH");
+    let runtime = runtime.unwrap();
+
+    let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+    let startup: Vec<_> = output
+        .lines()
+        .filter(|line| line.contains("AI runtime started"))
+        .collect();
+    assert_eq!(startup.len(), 1, "{output}");
+    assert!(
+        startup[0].contains(r#"pixel_backends=["cpu"]"#),
+        "{}",
+        startup[0]
+    );
+    assert!(startup[0].contains("gpu=none"), "{}", startup[0]);
+    assert!(
+        startup[0].contains(&format!("db_path={}", db_path.display())),
+        "{}",
+        startup[0]
+    );
+
+    let report = runtime.startup_report();
+    assert_eq!(report.db_path, db_path);
+    assert_eq!(report.pixel_backends, vec!["cpu"]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_pixel_canvas_limits() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = std::sync::Arc::new(AiRuntime::new().await.unwrap());
+    let limits = runtime.pixel_limits().clone();
+    let app = ai_runtime::api::ApiServer::router(runtime);
+    let run = |payload: serde_json::Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/api/pixel/run")
+                        .header("Content-Type", "application/json")
+                        .body(Body::from(payload.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let body: ai_runtime::PixelProgramResponse = serde_json::from_slice(&bytes).unwrap();
+            (status, body)
+        }
+    };
+    let program = vec![PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0)];
+
+    // Omitted sizes fall back to the configured defaults
+    let (status, body) = run(serde_json::json!({ "program": program })).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.success);
+    let default_pixels = limits.default_canvas_width * limits.default_canvas_height;
+    assert_eq!(body.canvas_data.len(), default_pixels as usize * 4);
+
+    let (status, body) = run(serde_json::json!({
+        "program": program,
+        "canvas_width": limits.max_canvas_width,
+        "canvas_height": 4
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.success);
+
+    let (status, body) = run(serde_json::json!({
+        "program": program,
+        "canvas_width": 8192,
+        "canvas_height": 8192
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body.error.as_deref(),
+        Some(
+            format!(
+                "canvas_width 8192 exceeds the maximum of {}",
+                limits.max_canvas_width
+            )
+            .as_str()
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_request_timeout_layer_stops_hung_handlers() {
+    use axum::routing::get;
+    use std::time::Duration;
+
+    let app = ai_runtime::api::with_request_timeout(
+        axum::Router::new()
+            .route(
+                "/hang",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    "finished"
+                }),
+            )
+            .route("/quick", get(|| async { "finished" })),
+        Duration::from_millis(50),
+    );
+    let request = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = tokio::time::timeout(
+        Duration::from_secs(5),
+        app.clone().oneshot(request("/hang")),
+    )
+    .await
+    .expect("timeout layer should answer before the handler finishes")
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+
+    let response = app.oneshot(request("/quick")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_pixel_run_region() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = std::sync::Arc::new(AiRuntime::new().await.unwrap());
+    let app = ai_runtime::api::ApiServer::router(runtime);
+    let payload = serde_json::json!({
+        "program": [
+            PixelInstruction::new(PixelOp::SET as u8, 9, 200, 0),
+            PixelInstruction::new(PixelOp::SET as u8, 30, 17, 0),
+            PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0),
+        ],
+        "canvas_width": 8,
+        "canvas_height": 8
+    })
+    .to_string();
+    let run = |uri: &str| {
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header("Content-Type", "application/json")
+            .body(Body::from(payload.clone()))
+            .unwrap();
+        let app = app.clone();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let region = response
+                .headers()
+                .get(ai_runtime::CANVAS_REGION_HEADER)
+                .map(|value| value.to_str().unwrap().to_string());
+            let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            let body: ai_runtime::PixelProgramResponse = serde_json::from_slice(&bytes).unwrap();
+            (status, region, body)
+        }
+    };
+
+    let (_, region, full) = run("/api/pixel/run").await;
+    assert_eq!(region, None);
+    assert_eq!(full.canvas_data.len(), 8 * 8 * 4);
+
+    let (status, region, partial) = run("/api/pixel/run?region=1,1,6,3").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(region.as_deref(), Some("1,1,6,3"));
+    let expected: Vec<u8> = (1..4)
+        .flat_map(|y| full.canvas_data[(y * 8 + 1) * 4..(y * 8 + 7) * 4].to_vec())
+        .collect();
+    assert_eq!(partial.canvas_data, expected);
+
+    // Regions past the edge are clamped to the canvas
+    let (_, region, edge) = run("/api/pixel/run?region=6,6,10,10").await;
+    assert_eq!(region.as_deref(), Some("6,6,2,2"));
+    assert_eq!(edge.canvas_data.len(), 2 * 2 * 4);
+
+    let (status, _, body) = run("/api/pixel/run?region=1,2").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.error.unwrap().contains("expected x,y,w,h"));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_review_negotiates_markdown() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = std::sync::Arc::new(AiRuntime::new().await.unwrap());
+    let app = ai_runtime::api::ApiServer::router(runtime);
+    let review = |accept: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/api/gvpie/review")
+                        .header("Accept", accept)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let content_type = response.headers()["content-type"]
+                .to_str()
+                .unwrap()
+                .to_string();
+            let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            (content_type, String::from_utf8(bytes.to_vec()).unwrap())
+        }
+    };
+
+    let (content_type, body) = review("text/markdown").await;
+    assert!(content_type.starts_with("text/markdown"));
+    assert!(body.starts_with("# Review comments"));
+
+    let (content_type, body) = review("application/json, */*;q=0.1").await;
+    assert_eq!(content_type, "application/json");
+    assert!(serde_json::from_str::<Vec<ai_runtime::gvpie_analysis::ReviewComment>>(&body).is_ok());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_executions_feed_instruction_frequency() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = AiRuntime::new().await.unwrap();
+    assert!(runtime.opcode_counts().is_empty());
+    let run = |program: Vec<PixelInstruction>| {
+        runtime.execute_pixel_program(PixelProgramRequest {
+            program,
+            backend: ExecutionBackend::Cpu,
+            max_cycles: 100,
+            canvas_width: 8,
+            canvas_height: 8,
+        })
+    };
+
+    let mut program = vec![PixelInstruction::new(PixelOp::SET as u8, 0, 1, 0); 3];
+    program.push(PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0));
+    assert!(run(program).await.unwrap().success);
+    let before = runtime.analyze_gvpie_codebase().await.unwrap();
+    let before = before.pixel_vm_analysis;
+    assert!(before.instruction_frequency_measured);
+    assert_eq!(before.instruction_frequency["PUTPIX"], 75);
+    assert!(!before.instruction_frequency.contains_key("FILL"));
+
+    let mut fills = vec![PixelInstruction::new(PixelOp::FILL as u8, 0, 9, 0); 12];
+    fills.push(PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0));
+    assert!(run(fills).await.unwrap().success);
+
+    let counts = runtime.opcode_counts();
+    assert_eq!(counts.executed[&PixelOp::FILL], 12);
+    assert_eq!(counts.executed[&PixelOp::SET], 3);
+    assert_eq!(counts.unattributed_steps, 0);
+
+    let after = runtime.analyze_gvpie_codebase().await.unwrap();
+    let frequency = &after.pixel_vm_analysis.instruction_frequency;
+    assert_eq!(frequency["FILL"], 71);
+    assert!(frequency["FILL"] > frequency["PUTPIX"]);
+    assert!(frequency["PUTPIX"] < before.instruction_frequency["PUTPIX"]);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_api_pixel_opcode_coverage() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let runtime = std::sync::Arc::new(AiRuntime::new().await.unwrap());
+    for op in [PixelOp::FILL, PixelOp::SET] {
+        let program = vec![
+            PixelInstruction::new(op as u8, 0, 1, 0),
+            PixelInstruction::new(op as u8, 1, 1, 0),
+            PixelInstruction::new(PixelOp::HALT as u8, 0, 0, 0),
+        ];
+        runtime
+            .execute_pixel_program(PixelProgramRequest {
+                program,
+                backend: ExecutionBackend::Cpu,
+                max_cycles: 100,
+                canvas_width: 8,
+                canvas_height: 8,
+            })
+            .await
+            .unwrap();
+    }
+
+    let coverage = runtime.opcode_coverage();
+    assert_eq!(coverage[&PixelOp::FILL], 2);
+    assert_eq!(coverage[&PixelOp::SET], 2);
+    assert_eq!(coverage[&PixelOp::JUMP], 0);
+    assert_eq!(coverage.len(), ai_runtime::pixel_vm::PIXEL_OPS.len());
+
+    let app = ai_runtime::api::ApiServer::router(runtime);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/pixel/coverage")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["opcodes"]["FILL"], 2);
+    assert_eq!(body["opcodes"]["PUTPIX"], 2);
+    assert_eq!(body["opcodes"]["JUMP"], 0);
+    assert_eq!(body["unattributed_steps"], 0);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_system_monitor_uses_configured_metrics() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("GVPIE_CARTRIDGE_PATH", temp_dir.path());
+    std::env::set_var("GVPIE_DB_PATH", ":memory:");
+    std::env::set_var("GVPIE_DISABLE_GPU", "1");
+
+    let config = ai_runtime::config::Config {
+        monitor: serde_yaml::from_str("metrics: [network, open_file_descriptors]").unwrap(),
+        ..Default::default()
+    };
+    let runtime = AiRuntime::with_config(config).await.unwrap();
+
+    let mut monitor = runtime.system_monitor();
+    assert_eq!(
+        monitor.tracked_metrics(),
+        &[
+            ai_runtime::TrackedMetric::Network,
+            ai_runtime::TrackedMetric::OpenFileDescriptors
+        ]
+    );
+    let metrics = monitor.capture_system_state();
+    assert!(metrics.extra.contains_key("network_rx_bytes"));
+}
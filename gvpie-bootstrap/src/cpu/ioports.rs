@@ -89,3 +89,256 @@ impl Uart16550 {
         }
     }
 }
+
+/// Input clock of a real 8254. The emulated PIT is instead clocked once per retired
+/// instruction, so reload values count instructions rather than wall time.
+pub const PIT_FREQUENCY_HZ: u32 = 1_193_182;
+
+/// Reload value giving `hz` expiries per second of a real 8254, rounded to the nearest
+/// divisor; rates too slow for 16 bits get 0, which counts 65536.
+pub fn pit_reload_for_hz(hz: u32) -> u16 {
+    if hz == 0 {
+        return 0;
+    }
+    let divisor = ((PIT_FREQUENCY_HZ + hz / 2) / hz).max(1);
+    if divisor > 0xffff {
+        0
+    } else {
+        divisor as u16
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct PitChannel {
+    /// Programmed reload value; 0 stands for 65536.
+    reload: u16,
+    count: u32,
+    mode: u8,
+    /// 1 = low byte only, 2 = high byte only, 3 = low then high.
+    access: u8,
+    /// The next low/high access targets the high byte.
+    high_next: bool,
+    latch: Option<u16>,
+    running: bool,
+}
+
+impl PitChannel {
+    fn period(&self) -> u32 {
+        if self.reload == 0 {
+            0x1_0000
+        } else {
+            self.reload as u32
+        }
+    }
+
+    fn load(&mut self) {
+        self.count = self.period();
+        self.running = true;
+    }
+
+    /// Count down by `ticks`, returning how many times the counter expired.
+    fn advance(&mut self, ticks: u64) -> u64 {
+        if !self.running || ticks == 0 {
+            return 0;
+        }
+        if ticks < self.count as u64 {
+            self.count -= ticks as u32;
+            return 0;
+        }
+        let past = ticks - self.count as u64;
+        match self.mode {
+            // Rate and square-wave generators reload and keep going.
+            2 | 3 => {
+                let period = self.period() as u64;
+                self.count = (period - past % period) as u32;
+                1 + past / period
+            }
+            // Everything else fires once on terminal count.
+            _ => {
+                self.count = 0;
+                self.running = false;
+                1
+            }
+        }
+    }
+}
+
+/// Minimal 8254 programmable interval timer on ports 0x40-0x43. Channel 0 drives IRQ0,
+/// which stays pending until taken with `take_irq0`.
+#[derive(Debug, Default)]
+pub struct Pit8254 {
+    channels: [PitChannel; 3],
+    irq0_pending: bool,
+}
+
+impl Pit8254 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn in8(&mut self, port: u16) -> u8 {
+        let Some(channel) = self.channels.get_mut(port.wrapping_sub(0x40) as usize) else {
+            return 0xff;
+        };
+        let value = channel.latch.unwrap_or(channel.count as u16);
+        let high = match channel.access {
+            2 => true,
+            3 => {
+                let high = channel.high_next;
+                channel.high_next = !high;
+                high
+            }
+            _ => false,
+        };
+        if high || channel.access == 1 {
+            channel.latch = None;
+        }
+        if high {
+            (value >> 8) as u8
+        } else {
+            value as u8
+        }
+    }
+
+    pub fn out8(&mut self, port: u16, value: u8) {
+        match port {
+            0x40..=0x42 => {
+                let channel = &mut self.channels[(port - 0x40) as usize];
+                match channel.access {
+                    1 => {
+                        channel.reload = value as u16;
+                        channel.load();
+                    }
+                    2 => {
+                        channel.reload = (value as u16) << 8;
+                        channel.load();
+                    }
+                    _ if !channel.high_next => {
+                        channel.reload = (channel.reload & 0xff00) | value as u16;
+                        channel.high_next = true;
+                    }
+                    _ => {
+                        channel.reload = (channel.reload & 0x00ff) | (value as u16) << 8;
+                        channel.high_next = false;
+                        channel.load();
+                    }
+                }
+            }
+            0x43 => {
+                // Read-back (select 3) is not emulated.
+                let Some(channel) = self.channels.get_mut((value >> 6) as usize) else {
+                    return;
+                };
+                match (value >> 4) & 0x3 {
+                    0 => channel.latch = Some(channel.count as u16),
+                    access => {
+                        channel.access = access;
+                        // Modes 6 and 7 alias 2 and 3.
+                        channel.mode = match (value >> 1) & 0x7 {
+                            mode @ 6..=7 => mode - 4,
+                            mode => mode,
+                        };
+                        channel.high_next = false;
+                        channel.latch = None;
+                        channel.running = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Advance every running channel by `ticks` input clocks.
+    pub fn tick(&mut self, ticks: u64) {
+        for (index, channel) in self.channels.iter_mut().enumerate() {
+            if channel.advance(ticks) > 0 && index == 0 {
+                self.irq0_pending = true;
+            }
+        }
+    }
+
+    /// Expiry rate a running channel would have on a real 8254, rounded to whole hertz.
+    pub fn frequency_hz(&self, channel: usize) -> Option<u32> {
+        let channel = self.channels.get(channel).filter(|channel| channel.running)?;
+        let period = channel.period();
+        Some((PIT_FREQUENCY_HZ + period / 2) / period)
+    }
+
+    pub fn irq0_pending(&self) -> bool {
+        self.irq0_pending
+    }
+
+    /// Acknowledge IRQ0, returning whether it was pending.
+    pub fn take_irq0(&mut self) -> bool {
+        std::mem::take(&mut self.irq0_pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Channel 0, low/high access, rate generator.
+    fn program_rate_generator(pit: &mut Pit8254, reload: u16) {
+        pit.out8(0x43, 0x34);
+        pit.out8(0x40, reload as u8);
+        pit.out8(0x40, (reload >> 8) as u8);
+    }
+
+    #[test]
+    fn rate_generator_raises_irq0_every_period() {
+        let mut pit = Pit8254::new();
+        program_rate_generator(&mut pit, 100);
+
+        pit.tick(99);
+        assert!(!pit.irq0_pending());
+        pit.tick(1);
+        assert!(pit.take_irq0());
+        assert!(!pit.take_irq0());
+
+        pit.tick(250);
+        assert!(pit.take_irq0());
+        // 350 ticks in: 50 left until the fourth expiry. Latch channel 0.
+        pit.out8(0x43, 0x00);
+        assert_eq!((pit.in8(0x40), pit.in8(0x40)), (50, 0));
+    }
+
+    #[test]
+    fn one_shot_fires_once_and_unprogrammed_channels_stay_quiet() {
+        let mut pit = Pit8254::new();
+        pit.tick(1_000_000);
+        assert!(!pit.irq0_pending());
+
+        // Channel 0, low byte only, interrupt on terminal count.
+        pit.out8(0x43, 0x10);
+        pit.out8(0x40, 10);
+        pit.tick(10);
+        assert!(pit.take_irq0());
+        pit.tick(1_000);
+        assert!(!pit.irq0_pending());
+    }
+
+    #[test]
+    fn zero_reload_counts_65536() {
+        let mut pit = Pit8254::new();
+        program_rate_generator(&mut pit, 0);
+
+        pit.tick(0xffff);
+        assert!(!pit.irq0_pending());
+        pit.tick(1);
+        assert!(pit.irq0_pending());
+    }
+
+    #[test]
+    fn reload_values_round_trip_through_the_input_clock() {
+        assert_eq!(pit_reload_for_hz(100), 11932);
+        assert_eq!(pit_reload_for_hz(10), 0);
+        assert_eq!(pit_reload_for_hz(PIT_FREQUENCY_HZ * 2), 1);
+
+        let mut pit = Pit8254::new();
+        assert_eq!(pit.frequency_hz(0), None);
+        program_rate_generator(&mut pit, pit_reload_for_hz(1000));
+        assert_eq!(pit.frequency_hz(0), Some(1000));
+        assert_eq!(pit.frequency_hz(3), None);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod ioports;
 pub mod stepper;
+pub mod syscall;
 
-pub use ioports::Uart16550;
-pub use stepper::{CpuState, InstructionStepper, StepAction};
+pub use ioports::{Pit8254, Uart16550};
+pub use stepper::{CpuState, InstructionStepper, StepAction, SyscallFrame};
+pub use syscall::{LinuxSyscallHandler, SyscallHandler, SyscallResult};
@@ -0,0 +1,550 @@
+//! Standalone x86-64 instruction stepper.
+//!
+//! Decodes a deliberately small subset of the ISA — enough to walk straight-line
+//! code up to a `syscall` — against a sparse, page-granular guest memory.
+
+use std::collections::BTreeMap;
+
+use super::ioports::Pit8254;
+use super::syscall::{SyscallHandler, SyscallResult};
+use crate::memory::{AddressSpaceId, MemoryBackend, Prot};
+
+pub const PAGE_SIZE: usize = 4096;
+/// Longest legal x86 instruction; the stepper always fetches this many bytes.
+pub const MAX_INSTRUCTION_LEN: usize = 15;
+
+const PAGE_MASK: u64 = !(PAGE_SIZE as u64 - 1);
+
+/// ENOSYS, returned when no syscall handler is registered.
+const ENOSYS: i64 = 38;
+
+struct Page {
+    data: [u8; PAGE_SIZE],
+    prot: Prot,
+}
+
+impl Page {
+    fn new(prot: Prot) -> Self {
+        Self { data: [0u8; PAGE_SIZE], prot }
+    }
+}
+
+/// Sparse guest memory backing the stepper. Unmapped bytes read as zero.
+///
+/// All address spaces share one flat page table; space ids are handed out for
+/// `MemoryBackend` callers but not isolated from each other. Permissions are
+/// tracked per page.
+#[derive(Default)]
+pub struct GPUMemoryManager {
+    pages: BTreeMap<u64, Page>,
+    /// Regions mapped through `map`, base -> length, so `resize` can unmap.
+    regions: BTreeMap<u64, usize>,
+    spaces: u32,
+    syscall_handler: Option<Box<dyn SyscallHandler>>,
+}
+
+impl GPUMemoryManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map (zeroed) every page overlapping `[addr, addr + len)` read/write/exec.
+    /// Already-mapped pages keep their contents and permissions.
+    pub fn map(&mut self, addr: u64, len: usize) {
+        self.map_with(addr, len, Prot::ALL);
+    }
+
+    /// Like [`map`](Self::map), with `prot` for newly mapped pages.
+    pub fn map_with(&mut self, addr: u64, len: usize, prot: Prot) {
+        if len == 0 {
+            return;
+        }
+        let region = self.regions.entry(addr).or_insert(0);
+        *region = (*region).max(len);
+        let mut page = addr & PAGE_MASK;
+        let end = addr.saturating_add(len as u64);
+        while page < end {
+            self.pages.entry(page).or_insert_with(|| Page::new(prot));
+            page = match page.checked_add(PAGE_SIZE as u64) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+    }
+
+    /// Page bases overlapping `[addr, addr + len)`.
+    fn pages_of(addr: u64, len: usize) -> impl Iterator<Item = u64> {
+        let first = addr & PAGE_MASK;
+        let end = addr.saturating_add(len as u64);
+        (0..)
+            .map(move |n: u64| first.saturating_add(n * PAGE_SIZE as u64))
+            .take_while(move |&page| len > 0 && page < end)
+    }
+
+    pub fn is_mapped(&self, addr: u64) -> bool {
+        self.pages.contains_key(&(addr & PAGE_MASK))
+    }
+
+    /// Permissions of the page holding `addr`, if it is mapped.
+    pub fn prot(&self, addr: u64) -> Option<Prot> {
+        self.pages.get(&(addr & PAGE_MASK)).map(|page| page.prot)
+    }
+
+    /// Set `prot` on every page overlapping `[addr, addr + len)`. All of them must be mapped.
+    pub fn protect(&mut self, addr: u64, len: usize, prot: Prot) -> Result<(), String> {
+        if let Some(page) = Self::pages_of(addr, len).find(|page| !self.pages.contains_key(page)) {
+            return Err(format!("protect of unmapped guest page 0x{page:x}"));
+        }
+        for page in Self::pages_of(addr, len) {
+            if let Some(page) = self.pages.get_mut(&page) {
+                page.prot = prot;
+            }
+        }
+        Ok(())
+    }
+
+    /// First mapped page in `[addr, addr + len)` whose permissions fail `allowed`.
+    fn denied_page(&self, addr: u64, len: usize, allowed: impl Fn(Prot) -> bool) -> Option<u64> {
+        Self::pages_of(addr, len).find(|page| self.pages.get(page).is_some_and(|p| !allowed(p.prot)))
+    }
+
+    /// Write `data` at `addr`, spanning page boundaries. Every touched page must
+    /// be mapped and writable; nothing is written otherwise.
+    pub fn write(&mut self, addr: u64, data: &[u8]) -> Result<(), String> {
+        if let Some(page) = self.denied_page(addr, data.len(), |prot| prot.write) {
+            return Err(format!("write to read-only guest page 0x{page:x}"));
+        }
+        let mut written = 0;
+        while written < data.len() {
+            let cursor = addr.wrapping_add(written as u64);
+            let offset = (cursor & !PAGE_MASK) as usize;
+            let chunk = (PAGE_SIZE - offset).min(data.len() - written);
+            let page = self
+                .pages
+                .get_mut(&(cursor & PAGE_MASK))
+                .ok_or_else(|| format!("write to unmapped guest address 0x{cursor:x}"))?;
+            page.data[offset..offset + chunk].copy_from_slice(&data[written..written + chunk]);
+            written += chunk;
+        }
+        Ok(())
+    }
+
+    pub fn set_syscall_handler(&mut self, handler: Box<dyn SyscallHandler>) {
+        self.syscall_handler = Some(handler);
+    }
+
+    /// Run the registered handler for `frame`. Without one every syscall fails with ENOSYS.
+    pub fn dispatch_syscall(&mut self, frame: &SyscallFrame) -> SyscallResult {
+        let Some(mut handler) = self.syscall_handler.take() else {
+            return SyscallResult::Return(-ENOSYS);
+        };
+        let result = handler.handle(frame, self);
+        self.syscall_handler = Some(handler);
+        result
+    }
+
+    /// Read `len` bytes starting at `rip`, zero-filling any unmapped gaps.
+    /// Fails if a mapped page in the range is not readable.
+    pub fn read_emulated_data(&self, rip: u64, len: usize) -> Result<Vec<u8>, String> {
+        if let Some(page) = self.denied_page(rip, len, |prot| prot.read) {
+            return Err(format!("read from unreadable guest page 0x{page:x}"));
+        }
+        Ok(self.read_unchecked(rip, len))
+    }
+
+    fn read_unchecked(&self, rip: u64, len: usize) -> Vec<u8> {
+        let mut out = vec![0u8; len];
+        let mut read = 0;
+        while read < len {
+            let cursor = rip.wrapping_add(read as u64);
+            let offset = (cursor & !PAGE_MASK) as usize;
+            let chunk = (PAGE_SIZE - offset).min(len - read);
+            if let Some(page) = self.pages.get(&(cursor & PAGE_MASK)) {
+                out[read..read + chunk].copy_from_slice(&page.data[offset..offset + chunk]);
+            }
+            read += chunk;
+        }
+        out
+    }
+}
+
+impl MemoryBackend for GPUMemoryManager {
+    fn create_address_space(&mut self) -> AddressSpaceId {
+        self.spaces += 1;
+        AddressSpaceId::from_raw(self.spaces)
+    }
+
+    fn map(&mut self, _space: AddressSpaceId, addr: u64, len: usize) -> Result<(), String> {
+        GPUMemoryManager::map(self, addr, len);
+        Ok(())
+    }
+
+    /// Unreadable pages read as zero, like unmapped ones.
+    fn read(&self, _space: AddressSpaceId, addr: u64, len: usize) -> Vec<u8> {
+        self.read_emulated_data(addr, len)
+            .unwrap_or_else(|_| vec![0u8; len])
+    }
+
+    fn write(&mut self, _space: AddressSpaceId, addr: u64, data: &[u8]) -> Result<(), String> {
+        GPUMemoryManager::write(self, addr, data)
+    }
+
+    fn protect(&mut self, _space: AddressSpaceId, addr: u64, len: usize, prot: Prot) -> Result<(), String> {
+        GPUMemoryManager::protect(self, addr, len, prot)
+    }
+
+    fn resize(&mut self, _space: AddressSpaceId, addr: u64, len: usize) -> Result<(), String> {
+        let old_len = self
+            .regions
+            .insert(addr, len)
+            .ok_or_else(|| format!("no region mapped at 0x{addr:x}"))?;
+        if len > old_len {
+            GPUMemoryManager::map(self, addr, len);
+            return Ok(());
+        }
+
+        let kept: Vec<(u64, usize)> = self.regions.iter().map(|(&base, &len)| (base, len)).collect();
+        for page in Self::pages_of(addr, old_len) {
+            let still_used = kept
+                .iter()
+                .any(|&(base, len)| Self::pages_of(base, len).any(|p| p == page));
+            if !still_used {
+                self.pages.remove(&page);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Architectural register file. General-purpose registers are indexed in
+/// x86 encoding order (rax=0 ... rdi=7, r8=8 ... r15=15).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CpuState {
+    pub gpr: [u64; 16],
+    pub rip: u64,
+    pub rflags: u64,
+}
+
+impl CpuState {
+    pub const RAX: usize = 0;
+    pub const RCX: usize = 1;
+    pub const RDX: usize = 2;
+    pub const RBX: usize = 3;
+    pub const RSP: usize = 4;
+    pub const RBP: usize = 5;
+    pub const RSI: usize = 6;
+    pub const RDI: usize = 7;
+    pub const R8: usize = 8;
+    pub const R9: usize = 9;
+    pub const R10: usize = 10;
+    pub const R11: usize = 11;
+
+    pub fn new(entry: u64) -> Self {
+        Self {
+            gpr: [0; 16],
+            rip: entry,
+            rflags: 0x2,
+        }
+    }
+}
+
+/// Syscall number and arguments per the Linux x86-64 calling convention.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyscallFrame {
+    pub space: AddressSpaceId,
+    pub number: u64,
+    /// rdi, rsi, rdx, r10, r8, r9.
+    pub args: [u64; 6],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepAction {
+    /// Instruction retired; keep stepping.
+    Continue,
+    /// A `syscall` retired; the caller should service it before stepping again.
+    SyscallTrap,
+    /// `hlt` retired.
+    Halt,
+    /// The stepper does not understand the bytes at `rip`.
+    InvalidOpcode { rip: u64, bytes: Vec<u8> },
+    /// The instruction at `rip` lies (at least partly) on the non-executable
+    /// page `page`. Nothing retired and the register file is unchanged.
+    ExecFault { rip: u64, page: u64 },
+    /// Hardware interrupt line `irq` is pending; nothing retired. The caller
+    /// should deliver it before stepping again.
+    Interrupt { irq: u8 },
+}
+
+pub struct InstructionStepper {
+    pub state: CpuState,
+    retired: u64,
+}
+
+impl InstructionStepper {
+    pub fn new(entry: u64) -> Self {
+        Self {
+            state: CpuState::new(entry),
+            retired: 0,
+        }
+    }
+
+    /// Decode the pending syscall from the register file. Meaningful after
+    /// `step_instruction` returns `SyscallTrap`.
+    pub fn current_syscall(&self, space: AddressSpaceId) -> SyscallFrame {
+        let gpr = &self.state.gpr;
+        SyscallFrame {
+            space,
+            number: gpr[CpuState::RAX],
+            args: [
+                gpr[CpuState::RDI],
+                gpr[CpuState::RSI],
+                gpr[CpuState::RDX],
+                gpr[CpuState::R10],
+                gpr[CpuState::R8],
+                gpr[CpuState::R9],
+            ],
+        }
+    }
+
+    /// Number of instructions retired so far.
+    pub fn retired(&self) -> u64 {
+        self.retired
+    }
+
+    /// `step_instruction` with `pit` clocked once per retired instruction. A
+    /// pending IRQ0 is reported before the next instruction is fetched.
+    pub fn step_with_timer(&mut self, mem: &GPUMemoryManager, pit: &mut Pit8254) -> StepAction {
+        if pit.take_irq0() {
+            return StepAction::Interrupt { irq: 0 };
+        }
+        let retired = self.retired;
+        let action = self.step_instruction(mem);
+        pit.tick(self.retired - retired);
+        action
+    }
+
+    pub fn step_instruction(&mut self, mem: &GPUMemoryManager) -> StepAction {
+        let rip = self.state.rip;
+        if let Some(page) = mem.denied_page(rip, 1, |prot| prot.exec) {
+            return StepAction::ExecFault { rip, page };
+        }
+        let bytes = mem.read_unchecked(rip, MAX_INSTRUCTION_LEN);
+        let saved = self.state.clone();
+
+        let (rex, opcode_at) = match bytes[0] {
+            rex @ 0x40..=0x4f => (rex, 1),
+            _ => (0, 0),
+        };
+        let rex_w = rex & 0x08 != 0;
+        let rex_b = (rex & 0x01) as usize;
+        let opcode = bytes[opcode_at];
+
+        let (action, len) = match opcode {
+            0x90 => (StepAction::Continue, opcode_at + 1),
+            0xf4 => (StepAction::Halt, opcode_at + 1),
+            0x0f if bytes[opcode_at + 1] == 0x05 => {
+                // syscall: rcx <- return rip, r11 <- rflags
+                let len = opcode_at + 2;
+                self.state.gpr[CpuState::RCX] = rip.wrapping_add(len as u64);
+                self.state.gpr[CpuState::R11] = self.state.rflags;
+                (StepAction::SyscallTrap, len)
+            }
+            0xb8..=0xbf => {
+                let reg = (opcode - 0xb8) as usize | (rex_b << 3);
+                let imm_at = opcode_at + 1;
+                if rex_w {
+                    let imm = u64::from_le_bytes(bytes[imm_at..imm_at + 8].try_into().unwrap());
+                    self.state.gpr[reg] = imm;
+                    (StepAction::Continue, imm_at + 8)
+                } else {
+                    let imm = u32::from_le_bytes(bytes[imm_at..imm_at + 4].try_into().unwrap());
+                    self.state.gpr[reg] = imm as u64;
+                    (StepAction::Continue, imm_at + 4)
+                }
+            }
+            0xeb => {
+                let rel = bytes[opcode_at + 1] as i8 as i64;
+                let len = opcode_at + 2;
+                if let Some(page) = mem.denied_page(rip, len, |prot| prot.exec) {
+                    return StepAction::ExecFault { rip, page };
+                }
+                self.state.rip = rip.wrapping_add(len as u64).wrapping_add(rel as u64);
+                self.retired += 1;
+                return StepAction::Continue;
+            }
+            _ => {
+                return StepAction::InvalidOpcode {
+                    rip,
+                    bytes: bytes[..opcode_at + 1].to_vec(),
+                }
+            }
+        };
+
+        // Instructions straddling into a non-executable page fault without retiring.
+        if let Some(page) = mem.denied_page(rip, len, |prot| prot.exec) {
+            self.state = saved;
+            return StepAction::ExecFault { rip, page };
+        }
+
+        self.state.rip = rip.wrapping_add(len as u64);
+        self.retired += 1;
+        action
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_span_page_boundaries() {
+        let mut mem = GPUMemoryManager::new();
+        let base = 0x4000 - 7;
+        mem.map(base, 16);
+        let data: Vec<u8> = (1..=15).collect();
+        mem.write(base, &data).unwrap();
+
+        assert!(mem.is_mapped(0x3000) && mem.is_mapped(0x4000));
+        assert_eq!(mem.read_emulated_data(base, 15).unwrap(), data);
+    }
+
+    #[test]
+    fn unmapped_bytes_read_as_zero() {
+        let mut mem = GPUMemoryManager::new();
+        mem.map(0x1000, PAGE_SIZE);
+        mem.write(0x1ffe, &[0xaa, 0xbb]).unwrap();
+
+        assert_eq!(mem.read_emulated_data(0x1ffe, 4).unwrap(), vec![0xaa, 0xbb, 0, 0]);
+        assert!(mem.write(0x2000, &[1]).is_err());
+    }
+
+    #[test]
+    fn shrinking_a_region_unmaps_only_its_own_pages() {
+        let mut mem = GPUMemoryManager::new();
+        let space = MemoryBackend::create_address_space(&mut mem);
+        MemoryBackend::map(&mut mem, space, 0x1000, 3 * PAGE_SIZE).unwrap();
+        MemoryBackend::map(&mut mem, space, 0x3800, 16).unwrap();
+
+        MemoryBackend::resize(&mut mem, space, 0x1000, 1).unwrap();
+        assert!(mem.is_mapped(0x1000));
+        assert!(!mem.is_mapped(0x2000));
+        // Still backs the second region.
+        assert!(mem.is_mapped(0x3000));
+
+        MemoryBackend::resize(&mut mem, space, 0x1000, 2 * PAGE_SIZE).unwrap();
+        assert!(mem.is_mapped(0x2000));
+        assert!(MemoryBackend::resize(&mut mem, space, 0x9000, 1).is_err());
+    }
+
+    #[test]
+    fn read_only_pages_reject_writes() {
+        let mut mem = GPUMemoryManager::new();
+        mem.map_with(0x1000, 2 * PAGE_SIZE, Prot::READ_WRITE);
+        mem.protect(0x2000, 1, Prot::READ).unwrap();
+
+        mem.write(0x1000, &[1, 2]).unwrap();
+        let err = mem.write(0x1ffe, &[3, 4, 5, 6]).unwrap_err();
+        assert!(err.contains("0x2000"), "{err}");
+        // A rejected write leaves the writable part untouched too.
+        assert_eq!(mem.read_emulated_data(0x1ffe, 4).unwrap(), vec![0, 0, 0, 0]);
+
+        mem.protect(0x1000, 1, Prot { read: false, write: false, exec: false }).unwrap();
+        assert!(mem.read_emulated_data(0x1000, 2).is_err());
+        assert!(mem.protect(0x9000, 1, Prot::READ).is_err());
+    }
+
+    #[test]
+    fn fetch_from_non_exec_page_faults() {
+        let mut mem = GPUMemoryManager::new();
+        // nop at the end of an executable page, then mov eax, 1 straddling into data.
+        mem.map_with(0x1000, PAGE_SIZE, Prot::READ_EXEC);
+        mem.map_with(0x2000, PAGE_SIZE, Prot::READ_WRITE);
+        mem.protect(0x1000, 1, Prot::ALL).unwrap();
+        mem.write(0x1ffd, &[0x90, 0xb8, 1, 0, 0, 0]).unwrap();
+        mem.protect(0x1000, 1, Prot::READ_EXEC).unwrap();
+
+        let mut stepper = InstructionStepper::new(0x1ffd);
+        assert_eq!(stepper.step_instruction(&mem), StepAction::Continue);
+        let before = stepper.state.clone();
+        assert_eq!(stepper.step_instruction(&mem), StepAction::ExecFault { rip: 0x1ffe, page: 0x2000 });
+        assert_eq!(stepper.state, before);
+        assert_eq!(stepper.retired(), 1);
+
+        stepper.state.rip = 0x2000;
+        assert_eq!(stepper.step_instruction(&mem), StepAction::ExecFault { rip: 0x2000, page: 0x2000 });
+    }
+
+    #[test]
+    fn steps_through_mov_and_syscall() {
+        let mut mem = GPUMemoryManager::new();
+        let entry = 0x40_0ffc;
+        // mov eax, 60; mov rdi, 7; syscall; hlt -- straddles a page boundary.
+        let code = [
+            0xb8, 60, 0, 0, 0, //
+            0x48, 0xbf, 7, 0, 0, 0, 0, 0, 0, 0, //
+            0x0f, 0x05, //
+            0xf4,
+        ];
+        mem.map(entry, code.len());
+        mem.write(entry, &code).unwrap();
+
+        let mut stepper = InstructionStepper::new(entry);
+        assert_eq!(stepper.step_instruction(&mem), StepAction::Continue);
+        assert_eq!(stepper.step_instruction(&mem), StepAction::Continue);
+        assert_eq!(stepper.step_instruction(&mem), StepAction::SyscallTrap);
+        assert_eq!(stepper.state.gpr[CpuState::RAX], 60);
+        assert_eq!(stepper.state.gpr[CpuState::RDI], 7);
+        assert_eq!(stepper.step_instruction(&mem), StepAction::Halt);
+        assert_eq!(stepper.retired(), 4);
+    }
+
+    #[test]
+    fn current_syscall_follows_linux_abi() {
+        let mut mem = GPUMemoryManager::new();
+        // mov eax, 1; mov edi, 2; mov esi, 0x1000; mov r10d, 5; syscall
+        let code = [
+            0xb8, 1, 0, 0, 0, //
+            0xbf, 2, 0, 0, 0, //
+            0xbe, 0x00, 0x10, 0, 0, //
+            0x41, 0xba, 5, 0, 0, 0, //
+            0x0f, 0x05,
+        ];
+        mem.map(0x1000, code.len());
+        mem.write(0x1000, &code).unwrap();
+
+        let mut stepper = InstructionStepper::new(0x1000);
+        while stepper.step_instruction(&mem) == StepAction::Continue {}
+
+        let space = crate::memory::Layer4Memory::new().create_address_space();
+        let frame = stepper.current_syscall(space);
+        assert_eq!(frame.space, space);
+        assert_eq!(frame.number, 1);
+        assert_eq!(frame.args, [2, 0x1000, 0, 5, 0, 0]);
+    }
+
+    #[test]
+    fn timer_interrupt_arrives_after_reload_instructions() {
+        let mut mem = GPUMemoryManager::new();
+        mem.map(0x1000, 16);
+        mem.write(0x1000, &[0x90; 16]).unwrap();
+
+        let mut pit = Pit8254::new();
+        // Channel 0, low/high access, rate generator, reload 3.
+        pit.out8(0x43, 0x34);
+        pit.out8(0x40, 3);
+        pit.out8(0x40, 0);
+
+        let mut stepper = InstructionStepper::new(0x1000);
+        for _ in 0..3 {
+            assert_eq!(stepper.step_with_timer(&mem, &mut pit), StepAction::Continue);
+        }
+        assert_eq!(stepper.step_with_timer(&mem, &mut pit), StepAction::Interrupt { irq: 0 });
+        assert_eq!(stepper.retired(), 3);
+        assert_eq!(stepper.state.rip, 0x1003);
+
+        for _ in 0..3 {
+            assert_eq!(stepper.step_with_timer(&mem, &mut pit), StepAction::Continue);
+        }
+        assert_eq!(stepper.step_with_timer(&mem, &mut pit), StepAction::Interrupt { irq: 0 });
+    }
+}
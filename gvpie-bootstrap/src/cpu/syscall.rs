@@ -0,0 +1,194 @@
+//! Syscall semantics for the standalone stepper.
+//!
+//! The stepper only reports `SyscallTrap`; what a syscall *does* is decided by
+//! the `SyscallHandler` registered on the `GPUMemoryManager`.
+
+use super::stepper::{GPUMemoryManager, SyscallFrame};
+use crate::memory::Prot;
+
+const SYS_READ: u64 = 0;
+const SYS_WRITE: u64 = 1;
+const SYS_BRK: u64 = 12;
+const SYS_EXIT: u64 = 60;
+const SYS_EXIT_GROUP: u64 = 231;
+
+const EBADF: i64 = 9;
+const EFAULT: i64 = 14;
+const ENOSYS: i64 = 38;
+
+/// Largest heap `brk` will grow to; larger requests leave the break unchanged.
+pub const MAX_HEAP_SIZE: u64 = 64 << 20;
+/// Bytes a single `write` consumes; longer writes are short writes, as on a pipe.
+pub const MAX_WRITE_LEN: u64 = 64 << 10;
+/// Console bytes retained; older output is dropped first.
+pub const CONSOLE_CAPACITY: usize = 1 << 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyscallResult {
+    /// Value to place in rax before resuming (negative errno on failure).
+    Return(i64),
+    /// The guest asked to terminate with this exit code.
+    Exit(i32),
+}
+
+pub trait SyscallHandler {
+    fn handle(&mut self, frame: &SyscallFrame, mem: &mut GPUMemoryManager) -> SyscallResult;
+}
+
+/// Minimal Linux personality: console write/read, exit and brk.
+pub struct LinuxSyscallHandler {
+    console: Vec<u8>,
+    brk_base: u64,
+    brk: u64,
+}
+
+impl LinuxSyscallHandler {
+    pub fn new(brk_base: u64) -> Self {
+        Self {
+            console: Vec::new(),
+            brk_base,
+            brk: brk_base,
+        }
+    }
+
+    /// The last `CONSOLE_CAPACITY` bytes written to stdout/stderr.
+    pub fn console(&self) -> &[u8] {
+        &self.console
+    }
+
+    fn write(&mut self, fd: u64, buf: u64, len: u64, mem: &GPUMemoryManager) -> i64 {
+        if fd != 1 && fd != 2 {
+            return -EBADF;
+        }
+        let len = len.min(MAX_WRITE_LEN);
+        if len > 0 && (!mem.is_mapped(buf) || !mem.is_mapped(buf.wrapping_add(len - 1))) {
+            return -EFAULT;
+        }
+        match mem.read_emulated_data(buf, len as usize) {
+            Ok(bytes) => self.console.extend_from_slice(&bytes),
+            Err(_) => return -EFAULT,
+        }
+        if self.console.len() > CONSOLE_CAPACITY {
+            let excess = self.console.len() - CONSOLE_CAPACITY;
+            self.console.drain(..excess);
+        }
+        len as i64
+    }
+
+    fn brk(&mut self, requested: u64, mem: &mut GPUMemoryManager) -> i64 {
+        if requested.saturating_sub(self.brk_base) > MAX_HEAP_SIZE {
+            return self.brk as i64;
+        }
+        if requested > self.brk {
+            mem.map_with(self.brk, (requested - self.brk) as usize, Prot::READ_WRITE);
+            self.brk = requested;
+        } else if requested >= self.brk_base {
+            self.brk = requested;
+        }
+        self.brk as i64
+    }
+}
+
+impl SyscallHandler for LinuxSyscallHandler {
+    fn handle(&mut self, frame: &SyscallFrame, mem: &mut GPUMemoryManager) -> SyscallResult {
+        let [a0, a1, a2, ..] = frame.args;
+        match frame.number {
+            SYS_WRITE => SyscallResult::Return(self.write(a0, a1, a2, mem)),
+            // No stdin is attached; reads from it see EOF.
+            SYS_READ if a0 == 0 => SyscallResult::Return(0),
+            SYS_READ => SyscallResult::Return(-EBADF),
+            SYS_BRK => SyscallResult::Return(self.brk(a0, mem)),
+            SYS_EXIT | SYS_EXIT_GROUP => SyscallResult::Exit(a0 as i32),
+            _ => SyscallResult::Return(-ENOSYS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::Layer4Memory;
+
+    fn frame(number: u64, args: [u64; 6]) -> SyscallFrame {
+        SyscallFrame {
+            space: Layer4Memory::new().create_address_space(),
+            number,
+            args,
+        }
+    }
+
+    #[test]
+    fn write_routes_buffer_to_console() {
+        let mut mem = GPUMemoryManager::new();
+        mem.map(0x2000, 16);
+        mem.write(0x2000, b"hello\n").unwrap();
+
+        let mut handler = LinuxSyscallHandler::new(0x10_0000);
+        let result = handler.handle(&frame(SYS_WRITE, [1, 0x2000, 6, 0, 0, 0]), &mut mem);
+        assert_eq!(result, SyscallResult::Return(6));
+        assert_eq!(handler.console(), b"hello\n");
+
+        let result = handler.handle(&frame(SYS_WRITE, [1, 0x9000, 4, 0, 0, 0]), &mut mem);
+        assert_eq!(result, SyscallResult::Return(-EFAULT));
+    }
+
+    #[test]
+    fn exit_dispatches_through_registered_handler() {
+        let mut mem = GPUMemoryManager::new();
+        assert_eq!(
+            mem.dispatch_syscall(&frame(SYS_EXIT, [3, 0, 0, 0, 0, 0])),
+            SyscallResult::Return(-ENOSYS)
+        );
+
+        mem.set_syscall_handler(Box::new(LinuxSyscallHandler::new(0x10_0000)));
+        assert_eq!(
+            mem.dispatch_syscall(&frame(SYS_EXIT, [3, 0, 0, 0, 0, 0])),
+            SyscallResult::Exit(3)
+        );
+    }
+
+    #[test]
+    fn brk_grows_and_maps_the_heap() {
+        let mut mem = GPUMemoryManager::new();
+        let mut handler = LinuxSyscallHandler::new(0x10_0000);
+        assert_eq!(
+            handler.handle(&frame(SYS_BRK, [0; 6]), &mut mem),
+            SyscallResult::Return(0x10_0000)
+        );
+        assert_eq!(
+            handler.handle(&frame(SYS_BRK, [0x10_2000, 0, 0, 0, 0, 0]), &mut mem),
+            SyscallResult::Return(0x10_2000)
+        );
+        assert!(mem.is_mapped(0x10_1fff));
+
+        let oversized = 0x10_0000 + MAX_HEAP_SIZE + 1;
+        assert_eq!(
+            handler.handle(&frame(SYS_BRK, [oversized, 0, 0, 0, 0, 0]), &mut mem),
+            SyscallResult::Return(0x10_2000)
+        );
+        assert!(!mem.is_mapped(0x10_2000));
+    }
+
+    #[test]
+    fn write_is_clamped_and_console_keeps_the_tail() {
+        let mut mem = GPUMemoryManager::new();
+        let len = MAX_WRITE_LEN as usize * 2;
+        mem.map(0x2000, len);
+        mem.write(0x2000, &vec![b'x'; len]).unwrap();
+
+        let mut handler = LinuxSyscallHandler::new(0x10_0000);
+        let request = frame(SYS_WRITE, [1, 0x2000, len as u64, 0, 0, 0]);
+        let result = handler.handle(&request, &mut mem);
+        assert_eq!(result, SyscallResult::Return(MAX_WRITE_LEN as i64));
+        assert_eq!(handler.console().len(), MAX_WRITE_LEN as usize);
+
+        mem.write(0x2000, b"tail").unwrap();
+        let writes = CONSOLE_CAPACITY / MAX_WRITE_LEN as usize;
+        for _ in 0..writes {
+            handler.handle(&frame(SYS_WRITE, [1, 0x2004, MAX_WRITE_LEN, 0, 0, 0]), &mut mem);
+        }
+        handler.handle(&frame(SYS_WRITE, [1, 0x2000, 4, 0, 0, 0]), &mut mem);
+        assert_eq!(handler.console().len(), CONSOLE_CAPACITY);
+        assert!(handler.console().ends_with(b"tail"));
+    }
+}
@@ -0,0 +1,121 @@
+//! Frame pacing for the bootstrap render loop.
+//!
+//! `about_to_wait` used to render back-to-back, spinning the CPU and GPU. The
+//! pacer caps the frame rate at a target FPS and reports how many emulation
+//! steps a frame should run so emulation speed tracks wall-clock time rather
+//! than the achieved frame rate.
+
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_TARGET_FPS: u32 = 60;
+
+/// Emulation steps per second of wall-clock time (one step per frame at 60 FPS).
+pub const EMULATION_STEPS_PER_SECOND: u32 = 60;
+
+/// Upper bound on steps run in one frame so a long stall cannot snowball.
+const MAX_STEPS_PER_FRAME: u32 = 16;
+
+/// Duration of a single frame at `target_fps`. A target of zero means uncapped.
+pub fn frame_interval(target_fps: u32) -> Duration {
+    if target_fps == 0 {
+        Duration::ZERO
+    } else {
+        Duration::from_secs_f64(1.0 / target_fps as f64)
+    }
+}
+
+/// Parse `--fps <n>` / `--fps=<n>` from the command line.
+pub fn parse_fps_arg<I: IntoIterator<Item = String>>(args: I) -> Option<u32> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--fps" {
+            return args.next().and_then(|value| value.parse().ok());
+        }
+        if let Some(value) = arg.strip_prefix("--fps=") {
+            return value.parse().ok();
+        }
+    }
+    None
+}
+
+pub struct FramePacer {
+    target_fps: u32,
+    last_frame: Option<Instant>,
+    step_debt: f64,
+}
+
+impl FramePacer {
+    pub fn new(target_fps: u32) -> Self {
+        Self {
+            target_fps,
+            last_frame: None,
+            step_debt: 0.0,
+        }
+    }
+
+    /// Instant at which the next frame is due, or `None` if it is due now.
+    pub fn next_deadline(&self, now: Instant) -> Option<Instant> {
+        let last = self.last_frame?;
+        let deadline = last + frame_interval(self.target_fps);
+        (deadline > now).then_some(deadline)
+    }
+
+    /// Mark the start of a frame and return the number of emulation steps to run.
+    pub fn begin_frame(&mut self, now: Instant) -> u32 {
+        let elapsed = match self.last_frame.replace(now) {
+            Some(last) => now.saturating_duration_since(last),
+            None => frame_interval(self.target_fps.max(1)),
+        };
+        self.steps_for_elapsed(elapsed)
+    }
+
+    fn steps_for_elapsed(&mut self, elapsed: Duration) -> u32 {
+        self.step_debt += elapsed.as_secs_f64() * EMULATION_STEPS_PER_SECOND as f64;
+        let steps = (self.step_debt.floor() as u32).min(MAX_STEPS_PER_FRAME);
+        self.step_debt = (self.step_debt - steps as f64).min(1.0);
+        steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_the_remainder_of_the_frame() {
+        let mut pacer = FramePacer::new(50);
+        let start = Instant::now();
+        assert_eq!(pacer.next_deadline(start), None);
+        pacer.begin_frame(start);
+        let deadline = pacer.next_deadline(start + Duration::from_millis(5));
+        assert_eq!(deadline, Some(start + Duration::from_millis(20)));
+    }
+
+    #[test]
+    fn slow_frames_are_due_immediately() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::new(60);
+        pacer.begin_frame(start);
+        assert_eq!(pacer.next_deadline(start + Duration::from_millis(40)), None);
+
+        let mut uncapped = FramePacer::new(0);
+        uncapped.begin_frame(start);
+        assert_eq!(uncapped.next_deadline(start + Duration::from_millis(1)), None);
+    }
+
+    #[test]
+    fn steps_scale_with_elapsed_time() {
+        let mut pacer = FramePacer::new(30);
+        let start = Instant::now();
+        pacer.begin_frame(start);
+        assert_eq!(pacer.begin_frame(start + Duration::from_millis(100)), 6);
+    }
+
+    #[test]
+    fn parses_fps_flag() {
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(parse_fps_arg(args(&["gvpie", "--fps", "30"])), Some(30));
+        assert_eq!(parse_fps_arg(args(&["gvpie", "--fps=144"])), Some(144));
+        assert_eq!(parse_fps_arg(args(&["gvpie"])), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! Validation error scopes around GPU submissions.
+
+use wgpu::{Device, ErrorFilter};
+
+/// Run `f` inside a `Validation` error scope and return whatever wgpu captured as an `Err`,
+/// instead of letting it reach the uncaptured-error handler (which panics by default).
+pub fn with_error_scope<T>(device: &Device, f: impl FnOnce() -> T) -> Result<T, String> {
+    device.push_error_scope(ErrorFilter::Validation);
+    let value = f();
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(err) => Err(format!("wgpu validation error: {err}")),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::{BufferDescriptor, BufferUsages, CommandEncoderDescriptor, DeviceDescriptor, Queue};
+
+    /// Any adapter will do, including the software fallback.
+    fn test_device() -> Option<(Device, Queue)> {
+        let instance = wgpu::Instance::default();
+        let adapter = crate::select_adapter(|force_fallback_adapter| {
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                force_fallback_adapter,
+                ..Default::default()
+            }))
+        })
+        .ok()?;
+        pollster::block_on(adapter.request_device(&DeviceDescriptor::default(), None)).ok()
+    }
+
+    #[test]
+    fn out_of_range_copy_is_an_error_not_a_panic() {
+        let Some((device, queue)) = test_device() else {
+            eprintln!("skipping: no wgpu adapter available");
+            return;
+        };
+        let buffer = |size| {
+            device.create_buffer(&BufferDescriptor {
+                label: None,
+                size,
+                usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        };
+        let (src, dst) = (buffer(16), buffer(16));
+
+        let result = with_error_scope(&device, || {
+            let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+            encoder.copy_buffer_to_buffer(&src, 0, &dst, 0, 64);
+            queue.submit([encoder.finish()]);
+        });
+        assert!(result.unwrap_err().contains("validation"));
+        assert_eq!(with_error_scope(&device, || 7), Ok(7));
+    }
+}
@@ -8,7 +8,8 @@ use wgpu::{
 
 use hybrid_canvas::{HybridCanvasBackend, TextRunOperation};
 
-use crate::text_cpu::CpuTextSurface;
+use crate::gpu_scope::with_error_scope;
+use crate::text_cpu::{CpuTextSurface, BACKGROUND_RGBA};
 
 pub struct WgpuHybridCanvas {
     device: Arc<Device>,
@@ -36,7 +37,8 @@ impl WgpuHybridCanvas {
         self.cpu = CpuTextSurface::new(self.width, self.height);
     }
 
-    pub fn present(&mut self, texture: &Texture) {
+    /// Upload the CPU surface into `texture`. wgpu validation failures come back as `Err`.
+    pub fn present(&mut self, texture: &Texture) -> Result<(), String> {
         let (w, h) = (self.width, self.height);
         let row_bytes = w * 4;
         let padded_row_bytes = ((row_bytes + 255) / 256) * 256;
@@ -50,47 +52,49 @@ impl WgpuHybridCanvas {
                 .copy_from_slice(&src[src_off..src_off + row_bytes as usize]);
         }
 
-        let staging = self.device.create_buffer_init(&util::BufferInitDescriptor {
-            label: Some("hybrid-canvas-staging"),
-            contents: &staged,
-            usage: BufferUsages::COPY_SRC,
-        });
-
-        let mut encoder = self
-            .device
-            .create_command_encoder(&CommandEncoderDescriptor {
-                label: Some("hybrid-canvas-encoder"),
+        with_error_scope(&self.device, || {
+            let staging = self.device.create_buffer_init(&util::BufferInitDescriptor {
+                label: Some("hybrid-canvas-staging"),
+                contents: &staged,
+                usage: BufferUsages::COPY_SRC,
             });
 
-        encoder.copy_buffer_to_texture(
-            ImageCopyBuffer {
-                buffer: &staging,
-                layout: ImageDataLayout {
-                    offset: 0,
-                    bytes_per_row: Some(padded_row_bytes),
-                    rows_per_image: Some(h),
+            let mut encoder = self
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("hybrid-canvas-encoder"),
+                });
+
+            encoder.copy_buffer_to_texture(
+                ImageCopyBuffer {
+                    buffer: &staging,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_row_bytes),
+                        rows_per_image: Some(h),
+                    },
+                },
+                ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
                 },
-            },
-            ImageCopyTexture {
-                texture,
-                mip_level: 0,
-                origin: Origin3d::ZERO,
-                aspect: TextureAspect::All,
-            },
-            Extent3d {
-                width: w,
-                height: h,
-                depth_or_array_layers: 1,
-            },
-        );
-
-        self.queue.submit([encoder.finish()]);
+                Extent3d {
+                    width: w,
+                    height: h,
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            self.queue.submit([encoder.finish()]);
+        })
     }
 }
 
 impl HybridCanvasBackend for WgpuHybridCanvas {
     fn begin_frame(&mut self) {
-        self.cpu.clear_rgba([0x12, 0x12, 0x16, 0xFF]);
+        self.cpu.clear_rgba(BACKGROUND_RGBA);
     }
 
     fn execute_text_run(&mut self, op: TextRunOperation) {
@@ -0,0 +1,74 @@
+//! Window-less canvas for builds without the `gpu` feature. Text runs are rasterized into the
+//! CPU surface and never uploaded, so no wgpu adapter or device is created.
+
+use hybrid_canvas::{HybridCanvasBackend, TextRunOperation};
+
+use crate::text_cpu::{CpuTextSurface, BACKGROUND_RGBA};
+
+pub struct HeadlessCanvas {
+    width: u32,
+    height: u32,
+    cpu: CpuTextSurface,
+}
+
+impl HeadlessCanvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width.max(1), height.max(1));
+        Self {
+            width,
+            height,
+            cpu: CpuTextSurface::new(width, height),
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn surface(&self) -> &CpuTextSurface {
+        &self.cpu
+    }
+}
+
+impl HybridCanvasBackend for HeadlessCanvas {
+    fn begin_frame(&mut self) {
+        self.cpu.clear_rgba(BACKGROUND_RGBA);
+    }
+
+    fn execute_text_run(&mut self, op: TextRunOperation) {
+        self.cpu
+            .draw_text(&op.text, op.x as i32, op.y as i32 + op.px_size as i32, op.px_size);
+    }
+
+    fn end_frame(&mut self) {}
+
+    fn resize(&mut self, width: u32, height: u32) {
+        *self = Self::new(width, height);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_renders_without_a_gpu() {
+        let mut canvas = HeadlessCanvas::new(64, 16);
+        canvas.begin_frame();
+        assert_eq!(canvas.surface().lit_pixels(), 0);
+        assert!(canvas.surface().bytes().chunks_exact(4).all(|px| px == BACKGROUND_RGBA));
+
+        canvas.cpu.draw_text("dir1", 0, 14, 7.0);
+        assert!(canvas.surface().lit_pixels() > 0);
+        canvas.end_frame();
+    }
+
+    #[test]
+    fn resize_clamps_and_clears() {
+        let mut canvas = HeadlessCanvas::new(8, 8);
+        canvas.cpu.draw_text("1", 0, 7, 7.0);
+        HybridCanvasBackend::resize(&mut canvas, 0, 4);
+        assert_eq!(canvas.size(), (1, 4));
+        assert_eq!(canvas.surface().lit_pixels(), 0);
+    }
+}
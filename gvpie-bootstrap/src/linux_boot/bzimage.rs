@@ -1,10 +1,15 @@
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
-
-use bytemuck::{Pod, Zeroable};
-
 use super::params::SetupHeader;
 
+/// Offset of `setup_header` from the start of the image.
+const SETUP_HEADER_OFFSET: usize = 0x1f1;
+/// "HdrS" in little-endian.
+const HDRS_MAGIC: u32 = 0x5372_6448;
+/// Oldest boot protocol we know how to load (2.11: 64-bit entry, ext fields).
+pub const MIN_BOOT_PROTOCOL: u16 = 0x020b;
+/// `loadflags` bit: protected-mode code is loaded at 0x100000.
+const LOADED_HIGH: u8 = 0x01;
+const SECTOR_SIZE: usize = 512;
+
 #[derive(Clone)]
 pub struct BzImage {
     pub kernel: Vec<u8>,
@@ -12,30 +17,107 @@ pub struct BzImage {
 }
 
 impl BzImage {
-    pub fn load(path: &str) -> Result<Self, String> {
-        let mut file = File::open(path).map_err(|e| e.to_string())?;
-        let mut kernel = Vec::new();
-        file.read_to_end(&mut kernel)
-            .map_err(|e| format!("read vmlinuz: {e}"))?;
-
-        // setup_header resides at offset 0x1f1 from start of file.
-        let mut header_bytes = [0u8; std::mem::size_of::<SetupHeader>()];
-        file.seek(SeekFrom::Start(0x1f1))
-            .map_err(|e| format!("seek header: {e}"))?;
-        file.read_exact(&mut header_bytes)
-            .map_err(|e| format!("read header: {e}"))?;
-        let header = *bytemuck::from_bytes::<SetupHeader>(&header_bytes);
-
-        if header.header != 0x5372_6448 {
+    /// Validate the setup header of an in-memory bzImage.
+    pub fn parse(kernel: Vec<u8>) -> Result<Self, String> {
+        let header_len = std::mem::size_of::<SetupHeader>();
+        let header_bytes = kernel
+            .get(SETUP_HEADER_OFFSET..SETUP_HEADER_OFFSET + header_len)
+            .ok_or_else(|| format!("bzImage too small for setup header: {} bytes", kernel.len()))?;
+        let header = *bytemuck::from_bytes::<SetupHeader>(header_bytes);
+
+        let magic = header.header;
+        if magic != HDRS_MAGIC {
             return Err("bzImage missing magic HdrS".into());
         }
-        if header.version < 0x020b {
+        let version = header.version;
+        if version < MIN_BOOT_PROTOCOL {
             return Err(format!(
-                "Linux boot protocol version too old: 0x{:x}",
-                header.version
+                "Linux boot protocol version too old: 0x{version:x} (minimum 0x{MIN_BOOT_PROTOCOL:x})"
             ));
         }
+        if header.loadflags & LOADED_HIGH == 0 {
+            return Err("not a bzImage: LOADED_HIGH is clear (zImage layout unsupported)".into());
+        }
+
+        let image = Self { kernel, header };
+        if image.code32_offset() >= image.kernel.len() {
+            return Err(format!(
+                "bzImage truncated: 32-bit kernel should start at 0x{:x} but image is {} bytes",
+                image.code32_offset(),
+                image.kernel.len()
+            ));
+        }
+        Ok(image)
+    }
+
+    /// Number of real-mode setup sectors (a value of 0 means 4, per boot.rst).
+    pub fn setup_sects(&self) -> usize {
+        match self.header.setup_sects {
+            0 => 4,
+            n => n as usize,
+        }
+    }
+
+    /// File offset where the 32-bit (protected-mode) kernel begins.
+    pub fn code32_offset(&self) -> usize {
+        (self.setup_sects() + 1) * SECTOR_SIZE
+    }
+
+    /// Physical load address of the 32-bit kernel advertised by the header.
+    pub fn code32_start(&self) -> u32 {
+        self.header.code32_start
+    }
+
+    /// The protected-mode kernel, i.e. everything after the real-mode setup code.
+    pub fn protected_mode_kernel(&self) -> &[u8] {
+        &self.kernel[self.code32_offset()..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crafted_image(version: u16, setup_sects: u8) -> Vec<u8> {
+        let sects = if setup_sects == 0 {
+            4
+        } else {
+            setup_sects as usize
+        };
+        let mut image = vec![0u8; (sects + 1) * SECTOR_SIZE + 64];
+        image[0x1f1] = setup_sects;
+        image[0x202..0x206].copy_from_slice(&HDRS_MAGIC.to_le_bytes());
+        image[0x206..0x208].copy_from_slice(&version.to_le_bytes());
+        image[0x211] = LOADED_HIGH;
+        image[0x214..0x218].copy_from_slice(&0x0010_0000u32.to_le_bytes());
+        image
+    }
+
+    #[test]
+    fn rejects_too_old_protocol() {
+        let err = BzImage::parse(crafted_image(0x0204, 4)).err().unwrap();
+        assert!(err.contains("too old"), "{err}");
+    }
+
+    #[test]
+    fn rejects_missing_magic() {
+        let mut image = crafted_image(0x020f, 4);
+        image[0x202] = 0;
+        assert!(BzImage::parse(image).is_err());
+    }
+
+    #[test]
+    fn parses_code32_start_and_offset() {
+        let image = BzImage::parse(crafted_image(0x020f, 2)).unwrap();
+        assert_eq!(image.code32_start(), 0x0010_0000);
+        assert_eq!(image.code32_offset(), 3 * SECTOR_SIZE);
+        assert_eq!(image.protected_mode_kernel().len(), 64);
+    }
 
-        Ok(Self { kernel, header })
+    #[test]
+    fn zero_setup_sects_means_four() {
+        let image = BzImage::parse(crafted_image(0x020f, 0)).unwrap();
+        assert_eq!(image.setup_sects(), 4);
+        assert_eq!(image.code32_offset(), 5 * SECTOR_SIZE);
     }
 }
@@ -3,10 +3,12 @@ mod params;
 
 use std::io::Read;
 
+use bytemuck::Zeroable;
 use bzimage::BzImage;
 use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
 
-use crate::memory::{AddressSpaceId, Layer4Memory};
+use crate::memory::{AddressSpaceId, MemoryBackend, Prot};
 
 pub use params::{BootParams, SetupHeader};
 
@@ -14,6 +16,30 @@ const BOOT_PARAMS_ADDR: u64 = 0x0009_0000;
 const CMDLINE_ADDR: u64 = 0x0009_A000;
 const KERNEL_LOAD_ADDR: u64 = 0x0010_0000;
 const INITRD_LOAD_ADDR: u64 = 0x0200_0000;
+/// Boot stack, growing down from just below the boot params.
+const BOOT_STACK_ADDR: u64 = 0x0008_0000;
+const BOOT_STACK_SIZE: usize = 0x1_0000;
+
+const DEFAULT_CMDLINE: &str =
+    "console=ttyS0,115200 earlyprintk=serial,ttyS0,115200 earlycon=uart,io,0x3f8,115200n8 loglevel=8";
+
+const TINYCORE_KERNEL: &str = "assets/tinycore/vmlinuz64";
+const TINYCORE_INITRD: &str = "assets/tinycore/corepure64.gz";
+/// Optional expected digests (hex) of the kernel and the compressed initrd as read from disk.
+const KERNEL_SHA256_ENV: &str = "GVPIE_KERNEL_SHA256";
+const INITRD_SHA256_ENV: &str = "GVPIE_INITRD_SHA256";
+
+/// Check `data` against an expected hex SHA256. `None` skips verification.
+fn verify_sha256(name: &str, data: &[u8], expected: Option<&str>) -> Result<(), String> {
+    let Some(expected) = expected.map(|digest| digest.trim().to_ascii_lowercase()) else {
+        return Ok(());
+    };
+    let actual: String = Sha256::digest(data).iter().map(|b| format!("{b:02x}")).collect();
+    if actual != expected {
+        return Err(format!("{name}: SHA256 mismatch (expected {expected}, got {actual})"));
+    }
+    Ok(())
+}
 
 pub struct LinuxBootLoader {
     bz: BzImage,
@@ -23,30 +49,75 @@ pub struct LinuxBootLoader {
 
 impl LinuxBootLoader {
     pub fn load_tinycore() -> Result<Self, String> {
-        let bz = BzImage::load("assets/tinycore/vmlinuz64")?;
-        let initrd_gz = std::fs::read("assets/tinycore/corepure64.gz")
-            .map_err(|e| format!("read corepure64.gz: {e}"))?;
+        let kernel = std::fs::read(TINYCORE_KERNEL).map_err(|e| format!("read vmlinuz64: {e}"))?;
+        verify_sha256(TINYCORE_KERNEL, &kernel, std::env::var(KERNEL_SHA256_ENV).ok().as_deref())?;
+        let bz = BzImage::parse(kernel)?;
+
+        let initrd_gz = std::fs::read(TINYCORE_INITRD).map_err(|e| format!("read corepure64.gz: {e}"))?;
+        verify_sha256(TINYCORE_INITRD, &initrd_gz, std::env::var(INITRD_SHA256_ENV).ok().as_deref())?;
         let mut decoder = GzDecoder::new(&initrd_gz[..]);
         let mut initrd = Vec::new();
         decoder
             .read_to_end(&mut initrd)
             .map_err(|e| format!("inflate corepure64.gz: {e}"))?;
 
-        let cmdline = "console=ttyS0,115200 earlyprintk=serial,ttyS0,115200 earlycon=uart,io,0x3f8,115200n8 loglevel=8\0";
+        let cmdline =
+            std::env::var("GVPIE_KERNEL_CMDLINE").unwrap_or_else(|_| DEFAULT_CMDLINE.to_string());
 
-        Ok(Self {
+        Ok(Self::from_parts(bz, initrd).with_cmdline(cmdline))
+    }
+
+    fn from_parts(bz: BzImage, initrd: Vec<u8>) -> Self {
+        Self {
             bz,
             initrd,
-            cmdline: cmdline.to_string(),
-        })
+            cmdline: DEFAULT_CMDLINE.to_string(),
+        }
+    }
+
+    /// Replace the kernel command line. Length is checked against the setup
+    /// header in [`install`](Self::install), before anything is written.
+    pub fn with_cmdline(mut self, cmdline: String) -> Self {
+        self.cmdline = cmdline.trim_end_matches('\0').to_string();
+        self
+    }
+
+    pub fn cmdline(&self) -> &str {
+        &self.cmdline
     }
 
-    pub fn install(&self, mem: &mut Layer4Memory) -> Result<(AddressSpaceId, SetupHeader), String> {
+    /// Maximum command line length (excluding the NUL terminator) the kernel accepts.
+    /// `BzImage::parse` rejects protocols older than 2.06, which introduced `cmdline_size`.
+    fn cmdline_limit(&self) -> usize {
+        self.bz.header.cmdline_size as usize
+    }
+
+    fn validate_cmdline(&self) -> Result<(), String> {
+        let limit = self.cmdline_limit();
+        if self.cmdline.len() > limit {
+            return Err(format!(
+                "kernel command line is {} bytes, but the kernel accepts at most {limit}",
+                self.cmdline.len()
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn install<M: MemoryBackend>(&self, mem: &mut M) -> Result<(AddressSpaceId, SetupHeader), String> {
+        self.validate_cmdline()?;
+
         let pid = mem.create_address_space();
 
-        mem.map_and_write(pid, KERNEL_LOAD_ADDR, &self.bz.kernel)?;
+        let mut cmdline = self.cmdline.clone().into_bytes();
+        cmdline.push(0);
+
+        let kernel = self.bz.protected_mode_kernel();
+        mem.map_and_write(pid, KERNEL_LOAD_ADDR, kernel)?;
+        mem.protect(pid, KERNEL_LOAD_ADDR, kernel.len(), Prot::READ_EXEC)?;
+        mem.map(pid, BOOT_STACK_ADDR, BOOT_STACK_SIZE)?;
+        mem.protect(pid, BOOT_STACK_ADDR, BOOT_STACK_SIZE, Prot::READ_WRITE)?;
         mem.map_and_write(pid, INITRD_LOAD_ADDR, &self.initrd)?;
-        mem.map_and_write(pid, CMDLINE_ADDR, self.cmdline.as_bytes())?;
+        mem.map_and_write(pid, CMDLINE_ADDR, &cmdline)?;
 
         let mut params = BootParams::zeroed();
         params.hdr = self.bz.header;
@@ -64,11 +135,113 @@ impl LinuxBootLoader {
         params.hdr.ramdisk_size = params.ext_ramdisk_size;
 
         mem.map_and_write(pid, BOOT_PARAMS_ADDR, bytemuck::bytes_of(&params))?;
-        mem.lineage()
-            .push(format!(
-                "Boot env mapped: kernel=0x{KERNEL_LOAD_ADDR:08x}, initrd=0x{INITRD_LOAD_ADDR:08x}, cmdline=0x{CMDLINE_ADDR:08x}, params=0x{BOOT_PARAMS_ADDR:08x}"
+        mem.record(format!(
+                "Boot env mapped: kernel=0x{KERNEL_LOAD_ADDR:08x}, initrd=0x{INITRD_LOAD_ADDR:08x}, cmdline=0x{CMDLINE_ADDR:08x}, params=0x{BOOT_PARAMS_ADDR:08x}, stack=0x{BOOT_STACK_ADDR:08x}"
             ));
 
         Ok((pid, self.bz.header))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::stepper::GPUMemoryManager;
+    use crate::memory::Layer4Memory;
+
+    fn loader_with_cmdline_size(cmdline_size: u32) -> LinuxBootLoader {
+        let mut header = SetupHeader::zeroed();
+        header.header = 0x5372_6448;
+        header.version = 0x020f;
+        header.cmdline_size = cmdline_size;
+        // setup_sects == 0 means four setup sectors plus the boot sector.
+        let bz = BzImage {
+            kernel: vec![0x90; 5 * 512 + 16],
+            header,
+        };
+        LinuxBootLoader::from_parts(bz, vec![0xAB; 8])
+    }
+
+    #[test]
+    fn custom_cmdline_is_written_nul_terminated() {
+        let cmdline = "console=ttyS0 quiet".to_string();
+        let loader = loader_with_cmdline_size(2047).with_cmdline(cmdline.clone());
+        let mut mem = Layer4Memory::new();
+
+        let (pid, _) = loader.install(&mut mem).unwrap();
+
+        let written = mem.read(pid, CMDLINE_ADDR, cmdline.len() + 1);
+        assert_eq!(&written[..cmdline.len()], cmdline.as_bytes());
+        assert_eq!(written[cmdline.len()], 0);
+    }
+
+    fn assert_boot_image_installed<M: MemoryBackend>(mem: &mut M) {
+        let loader = loader_with_cmdline_size(2047);
+        let (pid, _) = loader.install(mem).unwrap();
+
+        assert_eq!(mem.read(pid, KERNEL_LOAD_ADDR, 16), vec![0x90; 16]);
+        assert_eq!(mem.read(pid, INITRD_LOAD_ADDR, 8), vec![0xAB; 8]);
+
+        let params_len = std::mem::size_of::<BootParams>();
+        let params_bytes = mem.read(pid, BOOT_PARAMS_ADDR, params_len);
+        let params: BootParams = bytemuck::pod_read_unaligned(&params_bytes);
+        let (boot_flag, ramdisk_image, ramdisk_size) =
+            (params.hdr.boot_flag, params.hdr.ramdisk_image, params.hdr.ramdisk_size);
+        assert_eq!(boot_flag, 0xAA55);
+        assert_eq!(ramdisk_image, INITRD_LOAD_ADDR as u32);
+        assert_eq!(ramdisk_size, 8);
+    }
+
+    #[test]
+    fn installs_into_host_memory() {
+        assert_boot_image_installed(&mut Layer4Memory::new());
+    }
+
+    #[test]
+    fn installs_into_paged_gpu_memory() {
+        assert_boot_image_installed(&mut GPUMemoryManager::new());
+    }
+
+    #[test]
+    fn kernel_is_read_exec_and_stack_read_write() {
+        let mut mem = GPUMemoryManager::new();
+        loader_with_cmdline_size(2047).install(&mut mem).unwrap();
+
+        assert_eq!(mem.prot(KERNEL_LOAD_ADDR), Some(Prot::READ_EXEC));
+        assert!(mem.write(KERNEL_LOAD_ADDR, &[0xcc]).is_err());
+
+        let stack_top = BOOT_STACK_ADDR + BOOT_STACK_SIZE as u64 - 8;
+        assert_eq!(mem.prot(stack_top), Some(Prot::READ_WRITE));
+        mem.write(stack_top, &[1; 8]).unwrap();
+    }
+
+    // SHA256("abc")
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn matching_digest_passes_and_unset_skips() {
+        verify_sha256("kernel", b"abc", Some(ABC_SHA256)).unwrap();
+        verify_sha256("kernel", b"abc", Some(&format!(" {}\n", ABC_SHA256.to_uppercase()))).unwrap();
+        verify_sha256("kernel", b"anything", None).unwrap();
+    }
+
+    #[test]
+    fn wrong_digest_reports_both_hashes() {
+        let expected = "00".repeat(32);
+        let err = verify_sha256("corepure64.gz", b"abc", Some(&expected)).unwrap_err();
+        assert!(err.contains("corepure64.gz"), "{err}");
+        assert!(err.contains(&expected), "{err}");
+        assert!(err.contains(ABC_SHA256), "{err}");
+    }
+
+    #[test]
+    fn overlong_cmdline_is_rejected_before_writing() {
+        let loader = loader_with_cmdline_size(8).with_cmdline("console=ttyS0,115200".to_string());
+        let mut mem = Layer4Memory::new();
+
+        let err = loader.install(&mut mem).err().unwrap();
+
+        assert!(err.contains("at most 8"), "{err}");
+        assert!(mem.lineage().is_empty(), "nothing should be mapped");
+    }
+}
@@ -29,6 +29,11 @@ pub struct SetupHeader {
     pub ext_loader_type: u8,
     pub cmd_line_ptr: u32,
     pub initrd_addr_max: u32,
+    pub kernel_alignment: u32,
+    pub relocatable_kernel: u8,
+    pub min_alignment: u8,
+    pub xloadflags: u16,
+    pub cmdline_size: u32,
 }
 
 /// boot_params structure passed to the kernel.
@@ -50,5 +55,5 @@ pub struct BootParams {
     pub ext_cmd_line_ptr: u32,
     pub _pad3: [u8; 0x68],
     pub hdr: SetupHeader,
-    pub _pad4: [u8; 0x2d0],
+    pub _pad4: [u8; 0x2c4],
 }
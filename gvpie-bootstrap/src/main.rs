@@ -1,21 +1,51 @@
+// `cpu`, `memory` and `linux_boot` (the standalone stepper and Linux boot loader) aren't driven
+// from `main` yet; declaring them keeps them and their tests building with the rest of the crate.
+#[allow(dead_code)]
+mod cpu;
+mod frame_pacing;
+#[cfg(feature = "gpu")]
+mod gpu_scope;
+#[cfg(feature = "gpu")]
 mod gvx_canvas;
+#[cfg(not(feature = "gpu"))]
+mod headless;
+#[allow(dead_code)]
+mod linux_boot;
+#[allow(dead_code)]
+mod memory;
 mod text_cpu;
 
+#[cfg(feature = "gpu")]
 use std::sync::Arc;
+#[cfg(feature = "gpu")]
+use std::time::Instant;
 
+#[cfg(feature = "gpu")]
+use frame_pacing::FramePacer;
+use frame_pacing::DEFAULT_TARGET_FPS;
+
+#[cfg(feature = "gpu")]
 use gvx_canvas::WgpuHybridCanvas;
 use gpu_memory_manager::{Architecture, GPUMemoryManager, GpuSyscallTrap};
+use hybrid_canvas::HybridCanvasBackend;
+#[cfg(feature = "gpu")]
 use wgpu::{
     CompositeAlphaMode, DeviceDescriptor, Instance, InstanceDescriptor, PresentMode, RequestAdapterOptions,
     Surface, SurfaceConfiguration, SurfaceError, SurfaceTargetUnsafe, TextureUsages,
 };
+#[cfg(feature = "gpu")]
 use winit::{
     application::ApplicationHandler,
     event::WindowEvent,
-    event_loop::{ActiveEventLoop, EventLoop},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
     window::{Window, WindowId, WindowAttributes},
 };
 
+/// Canvas size used when there is no window to take it from.
+#[cfg(not(feature = "gpu"))]
+const HEADLESS_SIZE: (u32, u32) = (640, 480);
+
+#[cfg(feature = "gpu")]
 struct BootstrapApp {
     window: Option<Window>,
     surface: Option<Surface<'static>>,
@@ -23,10 +53,12 @@ struct BootstrapApp {
     config: Option<SurfaceConfiguration>,
     manager: Option<GPUMemoryManager<WgpuHybridCanvas>>,
     trap: Option<GpuSyscallTrap>,
+    pacer: FramePacer,
 }
 
+#[cfg(feature = "gpu")]
 impl BootstrapApp {
-    fn new() -> Self {
+    fn new(target_fps: u32) -> Self {
         Self {
             window: None,
             surface: None,
@@ -34,33 +66,36 @@ impl BootstrapApp {
             config: None,
             manager: None,
             trap: None,
+            pacer: FramePacer::new(target_fps),
         }
     }
 }
 
-impl ApplicationHandler for BootstrapApp {
-    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        if self.window.is_some() {
-            return;
-        }
-
+#[cfg(feature = "gpu")]
+impl BootstrapApp {
+    fn init_gpu(&mut self, event_loop: &ActiveEventLoop) -> Result<(), String> {
         let instance = Instance::new(InstanceDescriptor::default());
         let window_attrs = WindowAttributes::default().with_title("gvpie-bootstrap + GVX");
-        let window = event_loop.create_window(window_attrs).expect("window");
+        let window = event_loop
+            .create_window(window_attrs)
+            .map_err(|e| format!("create window: {e}"))?;
         self.window = Some(window);
         let window_ref = self.window.as_ref().expect("window stored");
         let size = window_ref.inner_size();
 
-        let target = unsafe { SurfaceTargetUnsafe::from_window(window_ref) }.expect("surface target");
-        let surface = unsafe { instance.create_surface_unsafe(target) }.expect("surface");
-        let adapter = pollster::block_on(instance.request_adapter(&RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        }))
-        .expect("adapter");
-        let (device_raw, queue_raw) =
-            pollster::block_on(adapter.request_device(&DeviceDescriptor::default(), None)).expect("device");
+        let target = unsafe { SurfaceTargetUnsafe::from_window(window_ref) }
+            .map_err(|e| format!("surface target: {e}"))?;
+        let surface = unsafe { instance.create_surface_unsafe(target) }
+            .map_err(|e| format!("create surface: {e}"))?;
+        let adapter = select_adapter(|force_fallback_adapter| {
+            pollster::block_on(instance.request_adapter(&RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                compatible_surface: Some(&surface),
+                force_fallback_adapter,
+            }))
+        })?;
+        let (device_raw, queue_raw) = pollster::block_on(adapter.request_device(&DeviceDescriptor::default(), None))
+            .map_err(|e| format!("request device: {e}"))?;
         let device = Arc::new(device_raw);
         let queue = Arc::new(queue_raw);
         let caps = surface.get_capabilities(&adapter);
@@ -69,7 +104,8 @@ impl ApplicationHandler for BootstrapApp {
             .iter()
             .copied()
             .find(|f| f.is_srgb())
-            .unwrap_or(caps.formats[0]);
+            .or_else(|| caps.formats.first().copied())
+            .ok_or_else(|| "surface reports no supported formats".to_string())?;
 
         let config = SurfaceConfiguration {
             usage: TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT,
@@ -89,24 +125,56 @@ impl ApplicationHandler for BootstrapApp {
             config.width,
             config.height,
         ));
-        let pid = manager.create_process(Architecture::X86_64);
-        let base: u64 = 0x1000_0000;
-        let text = b"dir1 dir2\n";
-        manager.map_emulated_memory(pid, base, text.len());
-        manager.write_emulated_data(pid, base, text);
-        let trap = GpuSyscallTrap {
-            pid,
-            syscall_num: 1,
-            arg1: 1,
-            arg2: base,
-            arg3: text.len() as u64,
-        };
+        let trap = install_demo_write(&mut manager);
 
         self.trap = Some(trap);
         self.manager = Some(manager);
         self.config = Some(config);
         self.device = Some(device);
         self.surface = Some(surface);
+        Ok(())
+    }
+}
+
+/// Map the demo text into a fresh x86-64 process and return the `write(1, ..)` trap that prints it.
+fn install_demo_write<C: HybridCanvasBackend>(manager: &mut GPUMemoryManager<C>) -> GpuSyscallTrap {
+    let pid = manager.create_process(Architecture::X86_64);
+    let base: u64 = 0x1000_0000;
+    let text = b"dir1 dir2\n";
+    manager.map_emulated_memory(pid, base, text.len());
+    manager.write_emulated_data(pid, base, text);
+    GpuSyscallTrap {
+        pid,
+        syscall_num: 1,
+        arg1: 1,
+        arg2: base,
+        arg3: text.len() as u64,
+    }
+}
+
+/// Request a hardware adapter first, then retry with wgpu's fallback (software) adapter.
+///
+/// `request` is called with the value for `force_fallback_adapter`.
+#[cfg(feature = "gpu")]
+fn select_adapter<A>(mut request: impl FnMut(bool) -> Option<A>) -> Result<A, String> {
+    if let Some(adapter) = request(false) {
+        return Ok(adapter);
+    }
+    eprintln!("no hardware GPU adapter found, trying the fallback adapter");
+    request(true).ok_or_else(|| "no compatible GPU adapter found (hardware or fallback)".to_string())
+}
+
+#[cfg(feature = "gpu")]
+impl ApplicationHandler for BootstrapApp {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+
+        if let Err(err) = self.init_gpu(event_loop) {
+            eprintln!("gvpie-bootstrap: GPU initialisation failed: {err}");
+            event_loop.exit();
+        }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, window_id: WindowId, event: WindowEvent) {
@@ -132,6 +200,12 @@ impl ApplicationHandler for BootstrapApp {
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let now = Instant::now();
+        if let Some(deadline) = self.pacer.next_deadline(now) {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+            return;
+        }
+
         let (surface, device, config, manager, trap) = match (
             self.surface.as_ref(),
             self.device.as_ref(),
@@ -164,17 +238,81 @@ impl ApplicationHandler for BootstrapApp {
             }
         };
 
-        manager.begin_frame();
-        let _ = manager.handle_emulated_syscall(trap);
-        manager.end_frame();
-        manager.canvas_mut().present(&frame.texture);
+        // Only redraw when emulation advanced; otherwise re-present the last frame.
+        let steps = self.pacer.begin_frame(now);
+        if steps > 0 {
+            manager.begin_frame();
+            for _ in 0..steps {
+                let _ = manager.handle_emulated_syscall(trap);
+            }
+            manager.end_frame();
+        }
+        if let Err(err) = manager.canvas_mut().present(&frame.texture) {
+            eprintln!("present failed: {err}");
+        }
         frame.present();
+
+        if let Some(deadline) = self.pacer.next_deadline(Instant::now()) {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(deadline));
+        } else {
+            event_loop.set_control_flow(ControlFlow::Poll);
+        }
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(not(target_arch = "wasm32"), feature = "gpu"))]
 fn main() {
+    let target_fps = frame_pacing::parse_fps_arg(std::env::args()).unwrap_or(DEFAULT_TARGET_FPS);
     let event_loop = EventLoop::new().expect("event loop");
-    let mut app = BootstrapApp::new();
+    let mut app = BootstrapApp::new(target_fps);
     event_loop.run_app(&mut app).expect("run_app");
 }
+
+/// Headless run: emulate one second's worth of frames at the requested rate and report what was drawn.
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "gpu")))]
+fn main() {
+    let target_fps = frame_pacing::parse_fps_arg(std::env::args()).unwrap_or(DEFAULT_TARGET_FPS);
+    let (width, height) = HEADLESS_SIZE;
+    let mut manager = GPUMemoryManager::new(headless::HeadlessCanvas::new(width, height));
+    let trap = install_demo_write(&mut manager);
+    for _ in 0..target_fps {
+        manager.begin_frame();
+        let _ = manager.handle_emulated_syscall(&trap);
+        manager.end_frame();
+    }
+    let lit = manager.canvas_mut().surface().lit_pixels();
+    println!("gvpie-bootstrap (headless): {target_fps} frames, {lit} text pixels on a {width}x{height} canvas");
+}
+
+#[cfg(all(test, feature = "gpu"))]
+mod tests {
+    use super::select_adapter;
+
+    #[test]
+    fn select_adapter_prefers_hardware() {
+        let mut calls = Vec::new();
+        let adapter = select_adapter(|fallback| {
+            calls.push(fallback);
+            Some("hardware")
+        });
+        assert_eq!(adapter, Ok("hardware"));
+        assert_eq!(calls, vec![false]);
+    }
+
+    #[test]
+    fn select_adapter_tries_fallback_when_primary_is_missing() {
+        let mut calls = Vec::new();
+        let adapter = select_adapter(|fallback| {
+            calls.push(fallback);
+            fallback.then_some("fallback")
+        });
+        assert_eq!(adapter, Ok("fallback"));
+        assert_eq!(calls, vec![false, true]);
+    }
+
+    #[test]
+    fn select_adapter_reports_when_nothing_is_available() {
+        let adapter: Result<(), String> = select_adapter(|_| None);
+        assert!(adapter.unwrap_err().contains("no compatible GPU adapter"));
+    }
+}
@@ -3,6 +3,59 @@ use std::collections::{BTreeMap, HashMap};
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AddressSpaceId(u32);
 
+impl AddressSpaceId {
+    pub(crate) const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+}
+
+/// Page permissions of a guest mapping.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Prot {
+    pub read: bool,
+    pub write: bool,
+    pub exec: bool,
+}
+
+impl Prot {
+    pub const READ: Prot = Prot { read: true, write: false, exec: false };
+    pub const READ_WRITE: Prot = Prot { read: true, write: true, exec: false };
+    pub const READ_EXEC: Prot = Prot { read: true, write: false, exec: true };
+    pub const ALL: Prot = Prot { read: true, write: true, exec: true };
+}
+
+/// Guest memory the boot loader and CPU can be pointed at: host RAM for tests
+/// (`Layer4Memory`) or the paged GPU-side memory (`cpu::GPUMemoryManager`).
+pub trait MemoryBackend {
+    fn create_address_space(&mut self) -> AddressSpaceId;
+
+    /// Map a zeroed region `[addr, addr + len)`. Existing contents are kept.
+    fn map(&mut self, space: AddressSpaceId, addr: u64, len: usize) -> Result<(), String>;
+
+    /// Unmapped bytes read as zero.
+    fn read(&self, space: AddressSpaceId, addr: u64, len: usize) -> Vec<u8>;
+
+    /// Fails if any target byte is unmapped.
+    fn write(&mut self, space: AddressSpaceId, addr: u64, data: &[u8]) -> Result<(), String>;
+
+    /// Grow or shrink the region mapped at `addr` to `len` bytes.
+    fn resize(&mut self, space: AddressSpaceId, addr: u64, len: usize) -> Result<(), String>;
+
+    /// Change the permissions of `[addr, addr + len)`. Backends without page
+    /// permissions accept and ignore this.
+    fn protect(&mut self, _space: AddressSpaceId, _addr: u64, _len: usize, _prot: Prot) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Record a human-readable lineage entry, if the backend keeps one.
+    fn record(&mut self, _entry: String) {}
+
+    fn map_and_write(&mut self, space: AddressSpaceId, addr: u64, data: &[u8]) -> Result<(), String> {
+        self.map(space, addr, data.len())?;
+        self.write(space, addr, data)
+    }
+}
+
 #[derive(Default)]
 struct Segment {
     base: u64,
@@ -43,6 +96,19 @@ impl Layer4Memory {
         guest_addr: u64,
         bytes: &[u8],
     ) -> Result<(), String> {
+        self.map_segment(id, guest_addr, bytes.len())?
+            .data[..bytes.len()]
+            .copy_from_slice(bytes);
+        self.lineage.push(format!(
+            "map+write {:?}: addr=0x{guest_addr:08x}, len={}",
+            id,
+            bytes.len()
+        ));
+        Ok(())
+    }
+
+    /// Segment based at `guest_addr`, created or grown to at least `len` bytes.
+    fn map_segment(&mut self, id: AddressSpaceId, guest_addr: u64, len: usize) -> Result<&mut Segment, String> {
         let space = self
             .spaces
             .get_mut(&id)
@@ -52,18 +118,12 @@ impl Layer4Memory {
             .entry(guest_addr)
             .or_insert_with(|| Segment {
                 base: guest_addr,
-                data: vec![0u8; bytes.len()],
+                data: vec![0u8; len],
             });
-        if segment.data.len() < bytes.len() {
-            segment.data.resize(bytes.len(), 0);
+        if segment.data.len() < len {
+            segment.data.resize(len, 0);
         }
-        segment.data[..bytes.len()].copy_from_slice(bytes);
-        self.lineage.push(format!(
-            "map+write {:?}: addr=0x{guest_addr:08x}, len={}",
-            id,
-            bytes.len()
-        ));
-        Ok(())
+        Ok(segment)
     }
 
     pub fn read(&self, id: AddressSpaceId, guest_addr: u64, len: usize) -> Vec<u8> {
@@ -99,3 +159,78 @@ impl Layer4Memory {
         &mut self.lineage
     }
 }
+
+impl MemoryBackend for Layer4Memory {
+    fn create_address_space(&mut self) -> AddressSpaceId {
+        Layer4Memory::create_address_space(self)
+    }
+
+    fn map(&mut self, space: AddressSpaceId, addr: u64, len: usize) -> Result<(), String> {
+        self.map_segment(space, addr, len)?;
+        self.lineage
+            .push(format!("map {:?}: addr=0x{addr:08x}, len={len}", space));
+        Ok(())
+    }
+
+    fn read(&self, space: AddressSpaceId, addr: u64, len: usize) -> Vec<u8> {
+        Layer4Memory::read(self, space, addr, len)
+    }
+
+    fn write(&mut self, space: AddressSpaceId, addr: u64, data: &[u8]) -> Result<(), String> {
+        let in_segment = self.spaces.get(&space).is_some_and(|s| {
+            s.segments.range(..=addr).next_back().is_some_and(|(_, seg)| {
+                (addr - seg.base) as usize + data.len() <= seg.data.len()
+            })
+        });
+        if !in_segment {
+            return Err(format!(
+                "write of {} bytes to unmapped guest address 0x{addr:x} in {:?}",
+                data.len(),
+                space
+            ));
+        }
+        Layer4Memory::write(self, space, addr, data);
+        Ok(())
+    }
+
+    fn resize(&mut self, space: AddressSpaceId, addr: u64, len: usize) -> Result<(), String> {
+        let segment = self
+            .spaces
+            .get_mut(&space)
+            .and_then(|s| s.segments.get_mut(&addr))
+            .ok_or_else(|| format!("no region mapped at 0x{addr:x} in {:?}", space))?;
+        segment.data.resize(len, 0);
+        self.lineage
+            .push(format!("resize {:?}: addr=0x{addr:08x}, len={len}", space));
+        Ok(())
+    }
+
+    fn record(&mut self, entry: String) {
+        self.lineage.push(entry);
+    }
+
+    fn map_and_write(&mut self, space: AddressSpaceId, addr: u64, data: &[u8]) -> Result<(), String> {
+        Layer4Memory::map_and_write(self, space, addr, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_backend_maps_writes_and_resizes() {
+        let mut mem = Layer4Memory::new();
+        let space = MemoryBackend::create_address_space(&mut mem);
+
+        assert!(MemoryBackend::write(&mut mem, space, 0x1000, &[1]).is_err());
+        MemoryBackend::map(&mut mem, space, 0x1000, 4).unwrap();
+        MemoryBackend::write(&mut mem, space, 0x1002, &[7, 8]).unwrap();
+        assert_eq!(MemoryBackend::read(&mem, space, 0x1000, 4), vec![0, 0, 7, 8]);
+        assert!(MemoryBackend::write(&mut mem, space, 0x1003, &[1, 2]).is_err());
+
+        MemoryBackend::resize(&mut mem, space, 0x1000, 2).unwrap();
+        assert_eq!(MemoryBackend::read(&mem, space, 0x1002, 2), vec![0, 0]);
+        assert!(MemoryBackend::resize(&mut mem, space, 0x2000, 2).is_err());
+    }
+}
@@ -1,3 +1,8 @@
+/// Frame clear colour used by every canvas backend.
+pub const BACKGROUND_RGBA: [u8; 4] = [0x12, 0x12, 0x16, 0xFF];
+/// Colour of rasterized glyph pixels.
+pub const TEXT_RGBA: [u8; 4] = [0xF8, 0xF8, 0xF8, 0xFF];
+
 pub struct CpuTextSurface {
     w: u32,
     h: u32,
@@ -56,6 +61,11 @@ impl CpuTextSurface {
         &self.buf
     }
 
+    /// Number of pixels currently painted with `TEXT_RGBA`.
+    pub fn lit_pixels(&self) -> usize {
+        self.buf.chunks_exact(4).filter(|px| *px == TEXT_RGBA).count()
+    }
+
     fn fill_block(&mut self, x: i32, y: i32, w: i32, h: i32) {
         for dy in 0..h {
             for dx in 0..w {
@@ -63,10 +73,7 @@ impl CpuTextSurface {
                 let py = y + dy;
                 if px >= 0 && py >= 0 && (px as u32) < self.w && (py as u32) < self.h {
                     let idx = ((py as u32 * self.w + px as u32) * 4) as usize;
-                    self.buf[idx + 0] = 0xF8;
-                    self.buf[idx + 1] = 0xF8;
-                    self.buf[idx + 2] = 0xF8;
-                    self.buf[idx + 3] = 0xFF;
+                    self.buf[idx..idx + 4].copy_from_slice(&TEXT_RGBA);
                 }
             }
         }